@@ -0,0 +1,118 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lifegame::game::Game;
+use lifegame::rle::Rle;
+use lifegame::rule::Rule;
+
+use std::sync::mpsc::channel;
+
+const SIZES: [usize; 3] = [64, 256, 512];
+
+const ACORN: &str = "bo5b$3bo3b$2o2b3o!";
+const GLIDER: &str = "bo$2bo$3o!";
+
+/// A `size` x `size` field with about half of the cells alive, the same on every run.
+fn soup(size: usize) -> Vec<bool> {
+    let mut seed: u32 = 0x2545_f491;
+    (0..size * size).map(|_| {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed & 1 == 1
+    }).collect()
+}
+
+/// A `size` x `size` field with `body` stamped at every `(x, y)` of `at`.
+fn stamp(size: usize, body: &str, at: &[(usize, usize)]) -> Vec<bool> {
+    let cells = Rle::live_cells(&Rle::with_header(body)).unwrap();
+    let mut lives = vec![false; size * size];
+    for (x, y) in at {
+        for (dx, dy) in &cells {
+            lives[(y + *dy as usize) * size + x + *dx as usize] = true;
+        }
+    }
+    lives
+}
+
+fn empty(size: usize) -> Vec<bool> {
+    vec![false; size * size]
+}
+
+/// An acorn in the middle, which keeps changing for 5206 generations.
+fn methuselah(size: usize) -> Vec<bool> {
+    stamp(size, ACORN, &[(size / 2 - 4, size / 2 - 2)])
+}
+
+/// Gliders every 8 cells in both directions.
+fn spaceships(size: usize) -> Vec<bool> {
+    let at: Vec<(usize, usize)> = (0..size / 8)
+        .flat_map(|y| (0..size / 8).map(move |x| (x * 8, y * 8)))
+        .collect();
+    stamp(size, GLIDER, &at)
+}
+
+/// Builds the cells of a `size` x `size` field.
+type Fill = fn(usize) -> Vec<bool>;
+
+const WORLDS: [(&str, Fill); 4] = [
+    ("empty", empty),
+    ("soup", soup),
+    ("methuselah", methuselah),
+    ("spaceships", spaceships),
+];
+
+/// Each world stepped by `Game::step` and `Game::step_farm`, in cells per second.
+fn backends(c: &mut Criterion) {
+    for (name, world) in WORLDS.iter() {
+        let mut group = c.benchmark_group(*name);
+
+        for size in SIZES.iter() {
+            let lives = world(*size);
+            group.throughput(Throughput::Elements((size * size) as u64));
+
+            group.bench_with_input(BenchmarkId::new("step", size), size, |b, &size| {
+                let mut game = Game::new(size, size, &lives);
+                b.iter(|| game.step());
+            });
+
+            group.bench_with_input(BenchmarkId::new("step_farm", size), size, |b, &size| {
+                let (trigger, triggers) = channel();
+                let (_game, results) = Game::new(size, size, &lives).step_farm(triggers);
+                results.recv().unwrap();
+
+                b.iter(|| {
+                    trigger.send(()).unwrap();
+                    results.recv().unwrap()
+                });
+            });
+        }
+        group.finish();
+    }
+}
+
+fn parse_rle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_rle");
+    for size in SIZES.iter() {
+        let body = Rle::encode(*size, *size, &soup(*size), &Rule::default());
+        group.throughput(Throughput::Bytes(body.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &body, |b, body| {
+            b.iter(|| Rle::from_string(body, 0).unwrap());
+        });
+    }
+    group.finish();
+}
+
+/// 100 generations of the heavy fixture, formerly `bench_work`.
+fn heavy(c: &mut Criterion) {
+    let (w, h, map) = Rle::from_file("./fixtures/heavy.rle", 10).unwrap();
+
+    let mut group = c.benchmark_group("heavy");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements((w * h * 100) as u64));
+    group.bench_function("100 generations", |b| {
+        b.iter(|| Game::new(w, h, &map).step_n(100));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, backends, parse_rle, heavy);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+/// Per-cell activity history, row-major like `World`.
+///
+/// `ages` counts the generations a cell has been alive (0 when dead) and
+/// `trails` counts down from `trail` once a cell dies, so recently dead cells
+/// can be drawn fading out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ages {
+    ages: Vec<u32>,
+    trails: Vec<u8>,
+    trail: u8,
+}
+
+impl Ages {
+    pub fn new<I: Iterator<Item=bool>>(lives: I, trail: u8) -> Ages {
+        let ages: Vec<u32> = lives.map(|doa| if doa { 1 } else { 0 }).collect();
+        let trails = vec![0; ages.len()];
+
+        Ages { ages, trails, trail }
+    }
+
+    /// Advances the history by one generation whose cells are `lives`.
+    pub fn update<I: Iterator<Item=bool>>(&mut self, lives: I) {
+        for ((age, trail), doa) in self.ages.iter_mut().zip(self.trails.iter_mut()).zip(lives) {
+            if doa {
+                *age = age.saturating_add(1);
+                *trail = 0;
+            } else if *age > 0 {
+                *age = 0;
+                *trail = self.trail;
+            } else {
+                *trail = trail.saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn ages(&self) -> &[u32] {
+        &self.ages
+    }
+
+    pub fn trails(&self) -> &[u8] {
+        &self.trails
+    }
+
+    pub fn trail(&self) -> u8 {
+        self.trail
+    }
+}
+
+#[test]
+fn test_update() {
+    let mut ages = Ages::new(vec![true, false, true].into_iter(), 2);
+    assert_eq!(ages.ages(), &[1, 0, 1]);
+
+    ages.update(vec![true, true, false].into_iter());
+    assert_eq!(ages.ages(), &[2, 1, 0]);
+    assert_eq!(ages.trails(), &[0, 0, 2]);
+
+    ages.update(vec![true, false, false].into_iter());
+    assert_eq!(ages.ages(), &[3, 0, 0]);
+    assert_eq!(ages.trails(), &[0, 2, 1]);
+
+    ages.update(vec![true, false, true].into_iter());
+    assert_eq!(ages.ages(), &[4, 0, 1]);
+    assert_eq!(ages.trails(), &[0, 1, 0]);
+}
@@ -0,0 +1,248 @@
+use crate::engine::{LifeEngine, Rect};
+use crate::game::Game;
+
+use std::collections::HashMap;
+
+/// A shape that came back after `period` generations, moved by `dx`, `dy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub period: usize,
+    pub dx: isize,
+    pub dy: isize,
+}
+
+impl Period {
+    pub fn is_still(&self) -> bool {
+        self.period == 1 && !self.moves()
+    }
+
+    pub fn moves(&self) -> bool {
+        self.dx != 0 || self.dy != 0
+    }
+
+    /// "still life", "oscillator" or "spaceship".
+    pub fn kind(&self) -> &'static str {
+        if self.is_still() {
+            "still life"
+        } else if self.moves() {
+            "spaceship"
+        } else {
+            "oscillator"
+        }
+    }
+}
+
+/// Watches generations for a shape that repeats, wherever it has moved to.
+///
+/// An empty field has no shape, so a pattern that dies out never gets a period.
+#[derive(Debug, Default)]
+pub struct Tracker {
+    /// Live cells relative to the bounding box, and the generation and position they were first seen at.
+    seen: HashMap<Vec<(isize, isize)>, (usize, isize, isize)>,
+    found: Option<Period>,
+}
+
+impl Tracker {
+    pub fn new() -> Tracker {
+        Tracker::default()
+    }
+
+    /// Records row-major `lives` of a field `width` cells wide as `generation`,
+    /// returning the period once one has been seen.
+    pub fn observe(&mut self, generation: usize, width: usize, lives: &[bool]) -> Option<Period> {
+        self.observe_cells(generation, lives.iter().enumerate()
+            .filter(|(_, doa)| **doa)
+            .map(|(i, _)| ((i % width) as isize, (i / width) as isize)))
+    }
+
+    /// Same as `observe` for the coordinates of the live cells.
+    pub fn observe_cells<I: Iterator<Item = (isize, isize)>>(&mut self, generation: usize, cells: I) -> Option<Period> {
+        if self.found.is_some() {
+            return self.found;
+        }
+
+        let cells: Vec<(isize, isize)> = cells.collect();
+        let Rect { x, y, .. } = Rect::around(cells.iter().cloned())?;
+        let shape = cells.iter().map(|(cx, cy)| (cx - x, cy - y)).collect();
+
+        match self.seen.insert(shape, (generation, x, y)) {
+            Some((first, fx, fy)) if first < generation => {
+                self.found = Some(Period { period: generation - first, dx: x - fx, dy: y - fy });
+                self.seen.clear();
+            }
+            _ => {}
+        }
+
+        self.found
+    }
+
+    pub fn period(&self) -> Option<Period> {
+        self.found
+    }
+}
+
+/// How a pattern ends up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fate {
+    /// Every cell is dead after this many generations.
+    Dies(usize),
+    /// The shape is seen again after this many generations.
+    Repeats(usize, Period),
+    /// Neither happened within the generations watched.
+    Unknown,
+}
+
+/// Dead cells `fate` keeps around the pattern it runs.
+const MARGIN: usize = 16;
+
+/// Runs a copy of `game` for up to `limit` generations to find its `Fate`.
+///
+/// The copy is moved to a field fitted around its live cells, `MARGIN` cells
+/// larger on every side, whenever it comes within two cells of an edge, so the
+/// edge of the field does not change the outcome however far the pattern goes.
+pub fn fate(game: &Game, limit: usize) -> Fate {
+    let (mut copy, mut origin) = refit(game, (0, 0));
+    let mut tracker = Tracker::new();
+
+    loop {
+        let generation = copy.generation();
+        let b = match copy.bounding_box() {
+            Some(b) => b,
+            None => return Fate::Dies(generation),
+        };
+        let (ox, oy) = origin;
+        if let Some(period) = tracker.observe_cells(generation, copy.live_cells().map(|(x, y)| (x as isize + ox, y as isize + oy))) {
+            return Fate::Repeats(generation, period);
+        }
+        if generation >= limit {
+            return Fate::Unknown;
+        }
+
+        let near_edge = b.x < 2 || b.y < 2
+            || b.x + b.width as isize + 2 > copy.width() as isize
+            || b.y + b.height as isize + 2 > copy.height() as isize;
+        if near_edge {
+            let (fitted, moved) = refit(&copy, origin);
+            copy = fitted;
+            origin = moved;
+        }
+        copy.step();
+    }
+}
+
+/// Copy of `game` on a field fitted around its live cells with `MARGIN` to spare,
+/// along with where that field starts relative to `origin`, the start of `game`.
+fn refit(game: &Game, (ox, oy): (isize, isize)) -> (Game, (isize, isize)) {
+    let b = game.bounding_box().unwrap_or(Rect { x: 0, y: 0, width: 0, height: 0 });
+    let (left, top) = (b.x - MARGIN as isize, b.y - MARGIN as isize);
+
+    let mut fitted = Game::new(b.width + MARGIN * 2, b.height + MARGIN * 2, &[]);
+    for (x, y) in game.live_cells() {
+        fitted.set_life(x as isize - left, y as isize - top, true);
+    }
+    fitted.set_rule(game.rule());
+    fitted.resume_at(game.generation());
+
+    (fitted, (ox + left, oy + top))
+}
+
+/// Smallest rectangle around the live cells of a field `width` cells wide.
+pub fn bounding_box(width: usize, lives: &[bool]) -> Option<Rect> {
+    Rect::around(lives.iter().enumerate()
+        .filter(|(_, doa)| **doa)
+        .map(|(i, _)| ((i % width) as isize, (i / width) as isize)))
+}
+
+#[cfg(test)]
+fn track(width: usize, height: usize, lives: &[bool], limit: usize) -> Option<Period> {
+    let mut game = Game::new(width, height, lives);
+    let mut tracker = Tracker::new();
+    for _ in 0..limit {
+        if let Some(period) = tracker.observe(game.generation(), width, &game.lives()) {
+            return Some(period);
+        }
+        game.step();
+    }
+    None
+}
+
+#[test]
+fn test_still_life() {
+    let block = vec![
+        false, false, false, false,
+        false, true, true, false,
+        false, true, true, false,
+        false, false, false, false,
+    ];
+
+    let period = track(4, 4, &block, 5).unwrap();
+    assert_eq!(period, Period { period: 1, dx: 0, dy: 0 });
+    assert_eq!(period.kind(), "still life");
+}
+
+#[test]
+fn test_oscillator() {
+    let mut blinker = vec![false; 25];
+    blinker[11..14].iter_mut().for_each(|doa| *doa = true);
+
+    let period = track(5, 5, &blinker, 5).unwrap();
+    assert_eq!(period, Period { period: 2, dx: 0, dy: 0 });
+    assert_eq!(period.kind(), "oscillator");
+}
+
+#[test]
+fn test_spaceship() {
+    let mut glider = vec![false; 100];
+    for (x, y) in &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+        glider[y * 10 + x] = true;
+    }
+
+    let period = track(10, 10, &glider, 10).unwrap();
+    assert_eq!(period, Period { period: 4, dx: 1, dy: 1 });
+    assert_eq!(period.kind(), "spaceship");
+}
+
+#[test]
+fn test_extinction_is_not_a_period() {
+    let domino = vec![
+        false, false, false, false,
+        false, true, true, false,
+        false, false, false, false,
+    ];
+
+    assert_eq!(track(4, 3, &domino, 5), None);
+}
+
+#[test]
+fn test_fate() {
+    let glider = vec![
+        false, true, false,
+        false, false, true,
+        true, true, true,
+    ];
+    assert_eq!(fate(&Game::new(3, 3, &glider), 10), Fate::Repeats(4, Period { period: 4, dx: 1, dy: 1 }));
+    assert_eq!(fate(&Game::new(3, 3, &glider), 3), Fate::Unknown);
+
+    let domino = vec![true, true];
+    assert_eq!(fate(&Game::new(2, 1, &domino), 10), Fate::Dies(1));
+
+    // Spreads well past the initial 8 x 3 field before it vanishes.
+    let diehard = vec![
+        false, false, false, false, false, false, true, false,
+        true, true, false, false, false, false, false, false,
+        false, true, false, false, false, true, true, true,
+    ];
+    assert_eq!(fate(&Game::new(8, 3, &diehard), 1000), Fate::Dies(130));
+}
+
+#[test]
+fn test_bounding_box() {
+    let lives = vec![
+        false, false, false,
+        false, true, false,
+        false, false, true,
+    ];
+
+    assert_eq!(bounding_box(3, &lives), Some(Rect { x: 1, y: 1, width: 2, height: 2 }));
+    assert_eq!(bounding_box(3, &[false; 9]), None);
+}
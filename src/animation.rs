@@ -0,0 +1,630 @@
+use crate::render::Style;
+use crate::standard_error::StandardError;
+
+use gif::SetParameter;
+use image_webp::{ColorType, WebPEncoder};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+/// Sink for the frames of an animation.
+///
+/// Frames are palette indices per cell of the field the writer was created for,
+/// drawn with its `Style`.
+pub trait AnimationWriter {
+    fn write_frame(&mut self, cells: &[u8]) -> Result<(), Box<dyn Error>>;
+
+    /// Pushes the frames written so far to the output, for formats that `Animation::resume` can continue.
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Flushes whatever the format can only write once every frame is known.
+    fn finish(self: Box<Self>) -> Result<(), Box<dyn Error>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Gif,
+    Apng,
+    WebP,
+}
+
+impl Format {
+    /// Picks the format from the extension: ".gif", ".png" / ".apng" or ".webp".
+    pub fn from_path(path: &str) -> Result<Format, StandardError> {
+        let extension = Path::new(path).extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("gif") => Ok(Format::Gif),
+            Some("png") | Some("apng") => Ok(Format::Apng),
+            Some("webp") => Ok(Format::WebP),
+            _ => Err(StandardError::new(&format!("unknown animation format: {:?}, use .gif, .png or .webp", path)))
+        }
+    }
+}
+
+/// Settings shared by every format.
+#[derive(Debug, Clone)]
+pub struct Animation {
+    /// Field size in cells.
+    pub width: usize,
+    pub height: usize,
+    /// Milliseconds per frame.
+    pub delay: u64,
+    /// Frames that will be written, which APNG has to declare up front.
+    pub frames: usize,
+    pub style: Style,
+}
+
+impl Animation {
+    pub fn create(&self, format: Format, output: &str) -> Result<Box<dyn AnimationWriter>, Box<dyn Error>> {
+        self.pixel_size()?;
+        if format == Format::Apng {
+            self.apng_frames()?;
+        }
+        self.writer(format, BufWriter::new(File::create(output)?))
+    }
+
+    /// Same as `create`, but keeps the first `frames` frames of the GIF already at `output`,
+    /// so a run resumed from a checkpoint carries on the animation it was writing.
+    /// APNG and WebP cannot be continued and only get the frames written from now on.
+    pub fn resume(&self, format: Format, output: &str, frames: usize) -> Result<Box<dyn AnimationWriter>, Box<dyn Error>> {
+        if format != Format::Gif || frames == 0 {
+            return self.create(format, output);
+        }
+
+        let size = self.pixel_size()?;
+        let earlier = match File::open(output) {
+            Ok(f) => read_gif(BufReader::new(f), size, frames),
+            Err(e) => Err(e.into()),
+        }.map_err(|e| StandardError::new(&format!("cannot continue {}: {}", output, e)))?;
+        let mut writer = GifWriter::new(BufWriter::new(File::create(output)?), self)?;
+        writer.replay(&earlier)?;
+
+        Ok(Box::new(writer))
+    }
+
+    /// Frame count for the acTL chunk, which an APNG declares up front and cannot be 0.
+    fn apng_frames(&self) -> Result<u32, Box<dyn Error>> {
+        match u32::try_from(self.frames) {
+            Ok(frames) if frames > 0 => Ok(frames),
+            _ => Err(Box::new(StandardError::new(&format!("an APNG cannot hold {} frames", self.frames)))),
+        }
+    }
+
+    /// Frame size in pixels, refusing a field without cells which no format can show.
+    fn pixel_size(&self) -> Result<(usize, usize), Box<dyn Error>> {
+        if self.width == 0 || self.height == 0 {
+            let message = format!("cannot animate an empty {} x {} field", self.width, self.height);
+            return Err(Box::new(StandardError::new(&message)));
+        }
+
+        Ok(self.style.size(self.width, self.height))
+    }
+
+    /// Same as `create` for any writer, such as a buffer or a socket.
+    pub fn writer<'a, W: Write + 'a>(&self, format: Format, w: W) -> Result<Box<dyn AnimationWriter + 'a>, Box<dyn Error>> {
+        match format {
+            Format::Gif => Ok(Box::new(GifWriter::new(w, self)?)),
+            Format::Apng => Ok(Box::new(ApngWriter::new(w, self)?)),
+            Format::WebP => Ok(Box::new(WebPWriter::new(w, self)?)),
+        }
+    }
+}
+
+/// Animated GIF whose frames after the first only cover the pixels that changed.
+///
+/// Each frame is kept on the canvas (disposal "keep") and pixels inside its
+/// rectangle that did not change are left transparent.
+pub struct GifWriter<W: Write> {
+    encoder: gif::Encoder<Shared<W>>,
+    output: Rc<RefCell<W>>,
+    animation: Animation,
+    frame_width: u16,
+    frame_height: u16,
+    transparent: u8,
+    previous: Option<Vec<u8>>,
+}
+
+impl<W: Write> GifWriter<W> {
+    pub fn new(w: W, animation: &Animation) -> Result<GifWriter<W>, Box<dyn Error>> {
+        let (pixel_width, pixel_height) = animation.pixel_size()?;
+        if pixel_width > u16::MAX as usize || pixel_height > u16::MAX as usize {
+            let message = format!("{} x {} pixels exceeds the GIF limit of 65535, narrow --view or --scale, or use .png", pixel_width, pixel_height);
+            return Err(Box::new(StandardError::new(&message)));
+        }
+        let (frame_width, frame_height) = (pixel_width as u16, pixel_height as u16);
+
+        let mut palette = animation.style.palette();
+        let transparent = (palette.len() / 3) as u8;
+        palette.extend(&[0, 0, 0]);
+
+        let output = Rc::new(RefCell::new(w));
+        let mut encoder = gif::Encoder::new(Shared(output.clone()), frame_width, frame_height, &palette)?;
+        encoder.set(gif::Repeat::Infinite)?;
+
+        Ok(GifWriter { encoder, output, animation: animation.clone(), frame_width, frame_height, transparent, previous: None })
+    }
+
+    /// Writes frames read back by `read_gif` as they are, and diffs the next frame against where they leave off.
+    fn replay(&mut self, frames: &[gif::Frame]) -> Result<(), Box<dyn Error>> {
+        let w = self.frame_width as usize;
+        let mut canvas = self.previous.take().unwrap_or_else(|| vec![0; w * self.frame_height as usize]);
+
+        for frame in frames {
+            for (i, index) in frame.buffer.iter().enumerate() {
+                if Some(*index) != frame.transparent {
+                    let (x, y) = (frame.left as usize + i % frame.width as usize, frame.top as usize + i / frame.width as usize);
+                    canvas[y * w + x] = *index;
+                }
+            }
+            self.encoder.write_frame(frame)?;
+        }
+
+        self.previous = Some(canvas);
+        Ok(())
+    }
+}
+
+/// Output of a `GifWriter`, shared with its encoder so that it can be flushed.
+struct Shared<W: Write>(Rc<RefCell<W>>);
+
+impl<W: Write> Write for Shared<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.borrow_mut().flush()
+    }
+}
+
+/// The first `frames` frames of a GIF written by `GifWriter` for a `width` x `height` pixel field.
+fn read_gif<R: Read>(r: R, (width, height): (usize, usize), frames: usize) -> Result<Vec<gif::Frame<'static>>, Box<dyn Error>> {
+    let mut decoder = gif::Decoder::new(r).read_info()?;
+    if (decoder.width() as usize, decoder.height() as usize) != (width, height) {
+        let message = format!("it is {} x {} pixels, not {} x {}", decoder.width(), decoder.height(), width, height);
+        return Err(Box::new(StandardError::new(&message)));
+    }
+
+    let mut read = Vec::with_capacity(frames);
+    while read.len() < frames {
+        match decoder.read_next_frame()? {
+            Some(frame) if frame.left as usize + frame.width as usize <= width && frame.top as usize + frame.height as usize <= height => {
+                read.push(frame.clone());
+            }
+            Some(_) => return Err(Box::new(StandardError::new("a frame lies outside the image"))),
+            None => {
+                let message = format!("it has {} of the {} frames before the checkpoint", read.len(), frames);
+                return Err(Box::new(StandardError::new(&message)));
+            }
+        }
+    }
+
+    Ok(read)
+}
+
+impl<W: Write> AnimationWriter for GifWriter<W> {
+    fn write_frame(&mut self, cells: &[u8]) -> Result<(), Box<dyn Error>> {
+        let a = &self.animation;
+        let pixels = a.style.rasterize_indexed(a.width, a.height, cells);
+
+        let mut frame = gif::Frame {
+            delay: (a.delay / 10).min(u16::MAX as u64) as u16,
+            dispose: gif::DisposalMethod::Keep,
+            ..gif::Frame::default()
+        };
+
+        match &self.previous {
+            None => {
+                frame.width = self.frame_width;
+                frame.height = self.frame_height;
+                frame.buffer = Cow::Borrowed(&*pixels);
+                self.encoder.write_frame(&frame)?;
+            }
+            Some(previous) => {
+                let w = self.frame_width as usize;
+                // An unchanged frame still has to be written for its delay, as a single pixel repainted in the colour it already has.
+                let (left, top, right, bottom) = changed_rect(w, previous, &pixels).unwrap_or((0, 0, 0, 0));
+
+                let size = (right - left + 1) * (bottom - top + 1);
+                let (mut plain, mut masked) = (Vec::with_capacity(size), Vec::with_capacity(size));
+                for y in top..=bottom {
+                    for x in left..=right {
+                        let i = y * w + x;
+                        plain.push(pixels[i]);
+                        masked.push(if pixels[i] == previous[i] { self.transparent } else { pixels[i] });
+                    }
+                }
+
+                // Masking unchanged pixels only pays off when it makes for longer runs.
+                let transparent = runs(&masked) < runs(&plain);
+                let patch = if transparent { masked } else { plain };
+
+                frame.left = left as u16;
+                frame.top = top as u16;
+                frame.width = (right - left + 1) as u16;
+                frame.height = (bottom - top + 1) as u16;
+                frame.transparent = if transparent { Some(self.transparent) } else { None };
+                frame.buffer = Cow::Borrowed(&*patch);
+                self.encoder.write_frame(&frame)?;
+            }
+        }
+
+        self.previous = Some(pixels);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        self.output.borrow_mut().flush()?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+pub struct ApngWriter<W: Write> {
+    writer: png::Writer<W>,
+    animation: Animation,
+    written: usize,
+}
+
+impl<W: Write> ApngWriter<W> {
+    pub fn new(w: W, animation: &Animation) -> Result<ApngWriter<W>, Box<dyn Error>> {
+        let (pixel_width, pixel_height) = animation.pixel_size()?;
+        let frames = animation.apng_frames()?;
+
+        let mut encoder = png::Encoder::new(w, pixel_width as u32, pixel_height as u32);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(animation.style.palette());
+        encoder.set_animated(frames, 0)?;
+        encoder.set_frame_delay(animation.delay.min(u16::MAX as u64) as u16, 1000)?;
+
+        Ok(ApngWriter { writer: encoder.write_header()?, animation: animation.clone(), written: 0 })
+    }
+}
+
+impl<W: Write> AnimationWriter for ApngWriter<W> {
+    fn write_frame(&mut self, cells: &[u8]) -> Result<(), Box<dyn Error>> {
+        let a = &self.animation;
+        self.writer.write_image_data(&a.style.rasterize_indexed(a.width, a.height, cells))?;
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), Box<dyn Error>> {
+        if self.written < self.animation.frames {
+            let message = format!("APNG declared {} frames but only {} were written", self.animation.frames, self.written);
+            return Err(Box::new(StandardError::new(&message)));
+        }
+        self.writer.finish()?;
+        Ok(())
+    }
+}
+
+/// Animated WebP made of lossless frames.
+///
+/// Frames are compressed as they come and the RIFF container is written by
+/// `finish`, since it starts with the total size. Until then every compressed
+/// frame is held in memory, so very long animations are better made as GIF or APNG.
+pub struct WebPWriter<W: Write> {
+    w: W,
+    animation: Animation,
+    palette: Vec<u8>,
+    frames: Vec<Vec<u8>>,
+}
+
+impl<W: Write> WebPWriter<W> {
+    pub fn new(w: W, animation: &Animation) -> Result<WebPWriter<W>, Box<dyn Error>> {
+        let (pixel_width, pixel_height) = animation.pixel_size()?;
+        if pixel_width > 1 << 14 || pixel_height > 1 << 14 {
+            let message = format!("{} x {} pixels exceeds the WebP limit of 16384", pixel_width, pixel_height);
+            return Err(Box::new(StandardError::new(&message)));
+        }
+
+        Ok(WebPWriter { w, animation: animation.clone(), palette: animation.style.palette(), frames: vec![] })
+    }
+}
+
+impl<W: Write> AnimationWriter for WebPWriter<W> {
+    fn write_frame(&mut self, cells: &[u8]) -> Result<(), Box<dyn Error>> {
+        let a = &self.animation;
+        let (pixel_width, pixel_height) = a.style.size(a.width, a.height);
+        let rgb: Vec<u8> = a.style.rasterize_indexed(a.width, a.height, cells).iter()
+            .flat_map(|i| self.palette[*i as usize * 3..*i as usize * 3 + 3].to_vec())
+            .collect();
+
+        let mut still = vec![];
+        WebPEncoder::new(&mut still).encode(&rgb, pixel_width as u32, pixel_height as u32, ColorType::Rgb8)?;
+
+        // A simple still is "RIFF" size "WEBP" followed by a single "VP8L" chunk.
+        self.frames.push(still[12..].to_vec());
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), Box<dyn Error>> {
+        let a = &self.animation;
+        let (pixel_width, pixel_height) = a.style.size(a.width, a.height);
+        let duration = a.delay.min(0xFF_FFFF) as u32;
+
+        let mut vp8x = vec![1 << 1, 0, 0, 0];
+        vp8x.extend(&u24(pixel_width as u32 - 1));
+        vp8x.extend(&u24(pixel_height as u32 - 1));
+
+        let mut chunks = chunk(b"VP8X", &vp8x);
+        chunks.extend(chunk(b"ANIM", &[0xFF, 0xFF, 0xFF, 0xFF, 0, 0]));
+        for frame in &self.frames {
+            let mut anmf = vec![0; 6];
+            anmf.extend(&u24(pixel_width as u32 - 1));
+            anmf.extend(&u24(pixel_height as u32 - 1));
+            anmf.extend(&u24(duration));
+            anmf.push(1 << 1);
+            anmf.extend(frame);
+            chunks.extend(chunk(b"ANMF", &anmf));
+        }
+
+        self.w.write_all(b"RIFF")?;
+        self.w.write_all(&(chunks.len() as u32 + 4).to_le_bytes())?;
+        self.w.write_all(b"WEBP")?;
+        self.w.write_all(&chunks)?;
+        self.w.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Inclusive `(left, top, right, bottom)` around the pixels that differ, if any.
+fn changed_rect(width: usize, a: &[u8], b: &[u8]) -> Option<(usize, usize, usize, usize)> {
+    let mut changed = a.iter().zip(b.iter()).enumerate()
+        .filter(|(_, (a, b))| a != b)
+        .map(|(i, _)| (i % width, i / width));
+    let (x, y) = changed.next()?;
+
+    Some(changed.fold((x, y, x, y), |(l, t, r, b), (x, y)| (l.min(x), t.min(y), r.max(x), b.max(y))))
+}
+
+fn runs(pixels: &[u8]) -> usize {
+    pixels.windows(2).filter(|pair| pair[0] != pair[1]).count()
+}
+
+fn u24(n: u32) -> [u8; 3] {
+    let b = n.to_le_bytes();
+    [b[0], b[1], b[2]]
+}
+
+fn chunk(name: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut c = name.to_vec();
+    c.extend(&(data.len() as u32).to_le_bytes());
+    c.extend(data);
+    if data.len() % 2 == 1 {
+        c.push(0);
+    }
+    c
+}
+
+#[cfg(test)]
+fn blinker_frames() -> (Animation, Vec<Vec<u8>>) {
+    let animation = Animation { width: 3, height: 3, delay: 100, frames: 2, style: Style { scale: 2, ..Style::default() } };
+    let frames = vec![
+        vec![0, 0, 0, 1, 1, 1, 0, 0, 0],
+        vec![0, 1, 0, 0, 1, 0, 0, 1, 0],
+    ];
+    (animation, frames)
+}
+
+#[cfg(test)]
+fn write_all<'a>(mut writer: Box<dyn AnimationWriter + 'a>, frames: &[Vec<u8>]) -> Box<dyn AnimationWriter + 'a> {
+    for frame in frames {
+        writer.write_frame(frame).unwrap();
+    }
+    writer
+}
+
+#[test]
+fn test_format_from_path() {
+    assert_eq!(Format::from_path("out.gif").unwrap(), Format::Gif);
+    assert_eq!(Format::from_path("out.PNG").unwrap(), Format::Apng);
+    assert_eq!(Format::from_path("dir.d/out.apng").unwrap(), Format::Apng);
+    assert_eq!(Format::from_path("out.webp").unwrap(), Format::WebP);
+    assert!(Format::from_path("out").is_err());
+    assert!(Format::from_path("out.mp4").is_err());
+}
+
+#[test]
+fn test_gif_writer() {
+    let (animation, frames) = blinker_frames();
+    let mut buf = vec![];
+    {
+        let writer = write_all(Box::new(GifWriter::new(&mut buf, &animation).unwrap()), &frames);
+        writer.finish().unwrap();
+    }
+
+    let canvases = decode_gif(&buf);
+    assert_eq!(canvases.len(), 2);
+    for (canvas, expected) in canvases.iter().zip(frames.iter()) {
+        assert_eq!(canvas[6 * 2], expected[3]);
+        assert_eq!(canvas[2], expected[1]);
+    }
+}
+
+/// Composites every frame onto the canvas the way a viewer would.
+#[cfg(test)]
+fn decode_gif(buf: &[u8]) -> Vec<Vec<u8>> {
+    let mut decoder = gif::Decoder::new(buf).read_info().unwrap();
+    let width = decoder.width() as usize;
+    let mut canvas = vec![0; width * decoder.height() as usize];
+    let mut canvases = vec![];
+
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        assert_eq!(frame.delay, 10);
+        for (i, index) in frame.buffer.iter().enumerate() {
+            if Some(*index) != frame.transparent {
+                let (x, y) = (frame.left as usize + i % frame.width as usize, frame.top as usize + i / frame.width as usize);
+                canvas[y * width + x] = *index;
+            }
+        }
+        canvases.push(canvas.clone());
+    }
+
+    canvases
+}
+
+#[test]
+fn test_gif_writer_diffs() {
+    use crate::game::Game;
+    use crate::render::{BACKGROUND, FOREGROUND};
+
+    let glider = vec![
+        false, true, false,
+        false, false, true,
+        true, true, true,
+    ];
+    let mut game = Game::new(12, 12, &glider);
+    let animation = Animation { width: 12, height: 12, delay: 100, frames: 30, style: Style::default() };
+
+    let mut buf = vec![];
+    let mut expected = vec![];
+    {
+        let mut writer = GifWriter::new(&mut buf, &animation).unwrap();
+        for _ in 0..30 {
+            let cells: Vec<u8> = game.lives().iter().map(|doa| if *doa { FOREGROUND } else { BACKGROUND }).collect();
+            writer.write_frame(&cells).unwrap();
+            expected.push(cells);
+            game.step();
+        }
+    }
+
+    let mut decoder = gif::Decoder::new(buf.as_slice()).read_info().unwrap();
+    decoder.read_next_frame().unwrap();
+    let second = decoder.read_next_frame().unwrap().unwrap();
+    assert!(second.width < 12 && second.height < 12);
+
+    assert_eq!(decode_gif(&buf), expected);
+}
+
+#[test]
+fn test_gif_writer_resume() {
+    use crate::game::Game;
+    use crate::render::{BACKGROUND, FOREGROUND};
+
+    let glider = vec![
+        false, true, false,
+        false, false, true,
+        true, true, true,
+    ];
+    let mut game = Game::new(12, 12, &glider);
+    let animation = Animation { width: 12, height: 12, delay: 100, frames: 20, style: Style::default() };
+    let frames: Vec<Vec<u8>> = (0..20).map(|_| {
+        let cells = game.lives().iter().map(|doa| if *doa { FOREGROUND } else { BACKGROUND }).collect();
+        game.step();
+        cells
+    }).collect();
+
+    let mut whole = vec![];
+    write_all(Box::new(GifWriter::new(&mut whole, &animation).unwrap()), &frames).finish().unwrap();
+
+    // Interrupted after 13 frames with a checkpoint taken after the 10th.
+    let mut interrupted = vec![];
+    {
+        let mut writer = GifWriter::new(&mut interrupted, &animation).unwrap();
+        for frame in &frames[..13] {
+            writer.write_frame(frame).unwrap();
+        }
+    }
+    let earlier = read_gif(interrupted.as_slice(), (12, 12), 10).unwrap();
+    assert!(read_gif(interrupted.as_slice(), (12, 12), 14).is_err());
+    assert!(read_gif(interrupted.as_slice(), (24, 24), 10).is_err());
+
+    let mut resumed = vec![];
+    {
+        let mut writer = GifWriter::new(&mut resumed, &animation).unwrap();
+        writer.replay(&earlier).unwrap();
+        write_all(Box::new(writer), &frames[10..]).finish().unwrap();
+    }
+
+    assert_eq!(decode_gif(&resumed), frames);
+    assert_eq!(resumed, whole);
+}
+
+#[test]
+fn test_empty_animation() {
+    let animation = Animation { width: 0, height: 3, delay: 100, frames: 1, style: Style::default() };
+
+    for format in &[Format::Gif, Format::Apng, Format::WebP] {
+        assert!(animation.writer(*format, vec![]).is_err());
+    }
+}
+
+#[test]
+fn test_apng_frame_count() {
+    let (animation, frames) = blinker_frames();
+
+    let none = Animation { frames: 0, ..animation.clone() };
+    assert!(ApngWriter::new(vec![], &none).is_err());
+
+    let writer = write_all(Box::new(ApngWriter::new(vec![], &animation).unwrap()), &frames[..1]);
+    assert!(writer.finish().is_err());
+}
+
+#[test]
+fn test_changed_rect() {
+    assert_eq!(changed_rect(3, &[0, 0, 0, 0, 0, 0], &[0, 0, 0, 0, 0, 0]), None);
+    assert_eq!(changed_rect(3, &[0, 0, 0, 0, 0, 0], &[0, 1, 0, 0, 0, 1]), Some((1, 0, 2, 1)));
+}
+
+#[test]
+fn test_apng_writer() {
+    let (animation, frames) = blinker_frames();
+    let mut buf = vec![];
+    {
+        let writer = write_all(Box::new(ApngWriter::new(&mut buf, &animation).unwrap()), &frames);
+        writer.finish().unwrap();
+    }
+
+    let mut reader = png::Decoder::new(buf.as_slice()).read_info().unwrap();
+    assert_eq!(reader.info().animation_control.unwrap().num_frames, 2);
+
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    for expected in &frames {
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(pixels[6 * 2], expected[3]);
+        assert_eq!(pixels[2], expected[1]);
+    }
+}
+
+#[test]
+fn test_webp_writer() {
+    use std::io::Cursor;
+
+    let (animation, frames) = blinker_frames();
+    let mut buf = vec![];
+    {
+        let writer = write_all(Box::new(WebPWriter::new(&mut buf, &animation).unwrap()), &frames);
+        writer.finish().unwrap();
+    }
+
+    let mut decoder = image_webp::WebPDecoder::new(Cursor::new(buf)).unwrap();
+    assert!(decoder.is_animated());
+    assert_eq!(decoder.num_frames(), 2);
+    assert_eq!(decoder.dimensions(), (6, 6));
+
+    let channels = if decoder.has_alpha() { 4 } else { 3 };
+    let mut pixels = vec![0; decoder.output_buffer_size().unwrap()];
+    for expected in &frames {
+        assert_eq!(decoder.read_frame(&mut pixels).unwrap(), 100);
+        let at = |x: usize, y: usize| pixels[(y * 6 + x) * channels];
+        assert_eq!(at(2, 0), if expected[1] == 1 { 0 } else { 0xFF });
+        assert_eq!(at(0, 2), if expected[3] == 1 { 0 } else { 0xFF });
+    }
+}
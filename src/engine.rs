@@ -0,0 +1,184 @@
+use crate::game::Game;
+use crate::rule::Rule;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: isize,
+    pub y: isize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    /// Smallest rectangle around the given cells.
+    pub fn around<I: Iterator<Item = (isize, isize)>>(mut cells: I) -> Option<Rect> {
+        let (x, y) = cells.next()?;
+
+        let (l, t, r, b) = cells.fold((x, y, x, y), |(l, t, r, b), (x, y)| {
+            (l.min(x), t.min(y), r.max(x), b.max(y))
+        });
+
+        Some(Rect { x: l, y: t, width: (r - l + 1) as usize, height: (b - t + 1) as usize })
+    }
+}
+
+/// A steppable universe.
+///
+/// Coordinates are `(x, y)` with `(0, 0)` at the top left of a `width` x `height` field.
+/// Only `step` and the cell accessors are required; the rest have naive defaults
+/// which implementations are free to replace with faster ones.
+pub trait LifeEngine {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn generation(&self) -> usize;
+    fn rule(&self) -> Rule;
+
+    fn is_live(&self, x: isize, y: isize) -> bool;
+    fn set_life(&mut self, x: isize, y: isize, doa: bool);
+
+    fn step(&mut self);
+
+    fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    fn live_cells(&self) -> Box<dyn Iterator<Item=(isize, isize)> + '_> {
+        let (w, h) = (self.width() as isize, self.height() as isize);
+
+        Box::new((0..h).flat_map(move |y| (0..w).map(move |x| (x, y)))
+            .filter(move |(x, y)| self.is_live(*x, *y)))
+    }
+
+    fn population(&self) -> usize {
+        self.live_cells().count()
+    }
+
+    fn bounding_box(&self) -> Option<Rect> {
+        Rect::around(self.live_cells())
+    }
+
+    /// Row-major alive/dead flags of the whole field.
+    fn lives(&self) -> Vec<bool> {
+        let (w, h) = (self.width() as isize, self.height() as isize);
+
+        (0..h).flat_map(|y| (0..w).map(move |x| (x, y)))
+            .map(|(x, y)| self.is_live(x, y))
+            .collect()
+    }
+}
+
+impl LifeEngine for Game {
+    fn width(&self) -> usize {
+        self.width as usize
+    }
+
+    fn height(&self) -> usize {
+        self.height as usize
+    }
+
+    fn generation(&self) -> usize {
+        Game::generation(self)
+    }
+
+    fn rule(&self) -> Rule {
+        Game::rule(self)
+    }
+
+    fn is_live(&self, x: isize, y: isize) -> bool {
+        Game::is_live(self, x, y)
+    }
+
+    fn set_life(&mut self, x: isize, y: isize, doa: bool) {
+        Game::set_life(self, x, y, doa)
+    }
+
+    fn step(&mut self) {
+        Game::step(self)
+    }
+
+    fn step_n(&mut self, n: usize) {
+        Game::step_n(self, n)
+    }
+
+    fn live_cells(&self) -> Box<dyn Iterator<Item=(isize, isize)> + '_> {
+        Box::new(Game::live_cells(self).map(|(x, y)| (x as isize, y as isize)))
+    }
+
+    fn population(&self) -> usize {
+        Game::population(self)
+    }
+
+    fn lives(&self) -> Vec<bool> {
+        Game::lives(self)
+    }
+}
+
+#[cfg(test)]
+fn glider() -> Game {
+    Game::new(6, 6, &vec![
+        false, true, false, false, false, false,
+        false, false, true, false, false, false,
+        true, true, true, false, false, false,
+    ])
+}
+
+#[test]
+fn test_live_cells() {
+    let g = glider();
+    let cells: Vec<(isize, isize)> = LifeEngine::live_cells(&g).collect();
+
+    assert_eq!(cells, vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+}
+
+#[test]
+fn test_bounding_box() {
+    let mut g = glider();
+    assert_eq!(LifeEngine::bounding_box(&g), Some(Rect { x: 0, y: 0, width: 3, height: 3 }));
+
+    LifeEngine::step_n(&mut g, 4);
+    assert_eq!(LifeEngine::bounding_box(&g), Some(Rect { x: 1, y: 1, width: 3, height: 3 }));
+
+    let empty = Game::new(3, 3, &[]);
+    assert_eq!(LifeEngine::bounding_box(&empty), None);
+}
+
+#[test]
+fn test_set_life() {
+    let mut g = Game::new(3, 3, &[]);
+    LifeEngine::set_life(&mut g, 1, 0, true);
+    LifeEngine::set_life(&mut g, 1, 1, true);
+    LifeEngine::set_life(&mut g, 1, 2, true);
+
+    assert_eq!(LifeEngine::population(&g), 3);
+    LifeEngine::step(&mut g);
+    assert!(LifeEngine::is_live(&g, 0, 1));
+    assert!(!LifeEngine::is_live(&g, 1, 0));
+}
+
+#[test]
+fn test_default_methods() {
+    struct Plain(Game);
+
+    impl LifeEngine for Plain {
+        fn width(&self) -> usize { self.0.width as usize }
+        fn height(&self) -> usize { self.0.height as usize }
+        fn generation(&self) -> usize { self.0.generation() }
+        fn rule(&self) -> Rule { self.0.rule() }
+        fn is_live(&self, x: isize, y: isize) -> bool { self.0.is_live(x, y) }
+        fn set_life(&mut self, x: isize, y: isize, doa: bool) { self.0.set_life(x, y, doa) }
+        fn step(&mut self) { self.0.step() }
+    }
+
+    let mut plain = Plain(glider());
+    let mut game = glider();
+
+    plain.step_n(3);
+    game.step_n(3);
+    assert_eq!(LifeEngine::lives(&plain), game.lives());
+    assert_eq!(LifeEngine::population(&plain), game.population());
+    assert_eq!(LifeEngine::bounding_box(&plain), LifeEngine::bounding_box(&game));
+    let cells: Vec<(isize, isize)> = plain.live_cells().collect();
+    assert_eq!(cells, LifeEngine::live_cells(&game).collect::<Vec<_>>());
+}
@@ -1,11 +1,18 @@
 extern crate num_cpus;
 
-use crate::world::World;
+use crate::world::{LiveCells, World};
+use crate::engine::Rect;
+use crate::history::History;
+use crate::rule::Rule;
+use crate::age::Ages;
 use std::mem::swap;
 use std::thread::spawn;
 use std::sync::{Arc, RwLock};
 use std::sync::mpsc::{Receiver, channel};
 
+/// Generation number and cells of each step taken by `Game::step_farm`.
+pub type Generations = Receiver<(usize, Vec<bool>)>;
+
 #[derive(Debug)]
 pub struct Game {
     pub width: isize,
@@ -14,16 +21,21 @@ pub struct Game {
     world_b: Arc<RwLock<World>>,
     cpu_num: usize,
     cpu_rows: usize,
+    generation: usize,
+    history: History,
+    rule: Rule,
+    ages: Option<Ages>,
 }
 
 impl Game {
-    pub fn new(width: usize, height: usize, lives: &Vec<bool>) -> Game {
+    pub fn new(width: usize, height: usize, lives: &[bool]) -> Game {
         let mut world_a = World::new(width, height);
         let world_b = World::new(width, height);
         let cpu_num = num_cpus::get();
         let cpu_rows = height / cpu_num + 1;
 
-        for (y, cols) in lives.chunks(width).enumerate() {
+        // A field without columns has no cells to set, and chunks(0) would panic.
+        for (y, cols) in lives.chunks(width.max(1)).enumerate() {
             for (x, doa) in cols.iter().enumerate() {
                 world_a.set_life(x as isize, y as isize, *doa);
             }
@@ -36,11 +48,55 @@ impl Game {
             world_b: Arc::new(RwLock::new(world_b)),
             cpu_num,
             cpu_rows,
+            generation: 0,
+            history: History::new(0, 0),
+            rule: Rule::default(),
+            ages: None,
         }
     }
 
-    pub fn step_farm(self, trigger_receiver: Receiver<()>) -> (Arc<RwLock<Game>>, Receiver<Vec<bool>>) {
-        let Game { cpu_num, width, height, cpu_rows, .. } = self;
+    /// Starts tracking how long cells have been alive, with dead cells fading over `trail` generations.
+    pub fn track_ages(&mut self, trail: u8) {
+        self.ages = Some(Ages::new(self.world_a.read().unwrap().iter(), trail));
+    }
+
+    pub fn ages(&self) -> Option<&Ages> {
+        self.ages.as_ref()
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Changing the rule forgets the history, which was computed with the old one.
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.history = History::new(self.history.depth(), self.generation);
+    }
+
+    /// Number of workers `step_farm` splits each generation across.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.cpu_num = threads.max(1);
+        self.cpu_rows = self.height as usize / self.cpu_num + 1;
+    }
+
+    /// Keeps up to `depth` past generations so they can be revisited with
+    /// `step_back` and `jump_to`. Zero disables the history.
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history = History::new(depth, self.generation);
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub(crate) fn resume_at(&mut self, generation: usize) {
+        self.generation = generation;
+        self.history = History::new(self.history.depth(), generation);
+    }
+
+    pub fn step_farm(self, trigger_receiver: Receiver<()>) -> (Arc<RwLock<Game>>, Generations) {
+        let Game { cpu_num, width, height, cpu_rows, rule, .. } = self;
 
         let workers = cpu_num;
         let cap = cpu_rows * width as usize;
@@ -70,7 +126,7 @@ impl Game {
                         }
                         rows += 1;
                         for x in 0..width {
-                            lives.push(next_live(&world_a, x as isize, y as isize));
+                            lives.push(next_live(&world_a, &rule, x, y as isize));
                         }
                     }
                     sender.send((head, rows, lives)).unwrap();
@@ -78,7 +134,10 @@ impl Game {
             });
         }
 
-        turn_end_sender.send(self_wrapper.read().unwrap().lives()).unwrap();
+        {
+            let game = self_wrapper.read().unwrap();
+            turn_end_sender.send((game.generation(), game.lives())).unwrap();
+        }
 
         let game_writer = self_wrapper.clone();
         spawn(move || {
@@ -93,8 +152,8 @@ impl Game {
 
                 if worked >= limit {
                     worked = 0;
-                    game.swap();
-                    turn_end_sender.send(game.lives()).unwrap();
+                    game.advance();
+                    turn_end_sender.send((game.generation(), game.lives())).unwrap();
                 } else {
                     worked += 1;
                 }
@@ -114,23 +173,138 @@ impl Game {
     }
 
     pub fn step(&mut self) {
+        if let Some(delta) = self.history.delta(self.generation) {
+            self.world_a.write().unwrap().toggle(delta);
+            self.generation += 1;
+            if let Some(ages) = &mut self.ages {
+                ages.update(self.world_a.read().unwrap().iter());
+            }
+            return;
+        }
+
         {
             let world_a = self.world_a.read().unwrap();
             let mut world_b = self.world_b.write().unwrap();
 
             for y in 0..self.height {
                 for x in 0..self.width {
-                    world_b.set_life(x, y, next_live(&world_a, x, y));
+                    world_b.set_life(x, y, next_live(&world_a, &self.rule, x, y));
                 }
             }
         }
 
-        self.swap();
+        self.advance();
+    }
+
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    /// Steps until `predicate` holds, checking the current generation first.
+    /// Returns the generation it held at, or `None` after `limit` steps without it.
+    pub fn run_until<P: FnMut(&Game) -> bool>(&mut self, mut predicate: P, limit: usize) -> Option<usize> {
+        for i in 0..=limit {
+            if predicate(self) {
+                return Some(self.generation);
+            }
+            if i < limit {
+                self.step();
+            }
+        }
+
+        None
+    }
+
+    /// Moves one generation back. Returns `false` when it is not retained.
+    /// Tracked ages restart from the restored generation.
+    pub fn step_back(&mut self) -> bool {
+        if self.generation == 0 {
+            return false;
+        }
+
+        match self.history.delta(self.generation - 1) {
+            Some(delta) => {
+                self.world_a.write().unwrap().toggle(delta);
+                self.generation -= 1;
+                if let Some(ages) = &self.ages {
+                    self.ages = Some(Ages::new(self.world_a.read().unwrap().iter(), ages.trail()));
+                }
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Moves to a generation within the retained history.
+    /// Returns `false` and stays put when `generation` is out of it.
+    pub fn jump_to(&mut self, generation: usize) -> bool {
+        if generation > self.history.newest().max(self.generation) {
+            return false;
+        }
+        if generation < self.generation && self.history.delta(generation).is_none() {
+            return false;
+        }
+
+        while self.generation > generation {
+            if !self.step_back() {
+                return false;
+            }
+        }
+        while self.generation < generation {
+            self.step();
+        }
+
+        true
     }
 
     pub fn lives(&self) -> Vec<bool> {
-        let world_a = self.world_a.read().unwrap();
-        world_a.cells.clone()
+        self.world_a.read().unwrap().to_vec()
+    }
+
+    /// Live cells in row-major order; the field stays read-locked until the iterator is dropped.
+    pub fn live_cells(&self) -> impl Iterator<Item=(usize, usize)> + '_ {
+        LiveCells::all(self.world_a.read().unwrap())
+    }
+
+    /// Live cells within `rect`, clipped to the field, in row-major order.
+    pub fn live_cells_in(&self, rect: Rect) -> impl Iterator<Item=(usize, usize)> + '_ {
+        LiveCells::within(self.world_a.read().unwrap(), rect)
+    }
+
+    pub fn is_live(&self, x: isize, y: isize) -> bool {
+        self.world_a.read().unwrap().is_live(x, y)
+    }
+
+    /// Editing a cell forgets the history, which no longer leads to the current world.
+    pub fn set_life(&mut self, x: isize, y: isize, doa: bool) {
+        self.world_a.write().unwrap().set_life(x, y, doa);
+        self.history = History::new(self.history.depth(), self.generation);
+    }
+
+    /// Same as `set_life` for every listed `(x, y)`.
+    pub fn set_cells(&mut self, coords: &[(isize, isize)], doa: bool) {
+        self.world_a.write().unwrap().set_cells(coords, doa);
+        self.history = History::new(self.history.depth(), self.generation);
+    }
+
+    pub fn population(&self) -> usize {
+        self.world_a.read().unwrap().population()
+    }
+
+    fn advance(&mut self) {
+        if self.history.depth() > 0 {
+            let delta = self.world_a.read().unwrap().changes(&self.world_b.read().unwrap());
+            self.history.record(self.generation, delta);
+        }
+
+        self.swap();
+        self.generation += 1;
+
+        if let Some(ages) = &mut self.ages {
+            ages.update(self.world_a.read().unwrap().iter());
+        }
     }
 
     fn swap(&mut self) {
@@ -140,7 +314,7 @@ impl Game {
 
 static POSES: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
 
-pub fn next_live(world: &World, x: isize, y: isize) -> bool {
+fn next_live(world: &World, rule: &Rule, x: isize, y: isize) -> bool {
     let now = world.is_live(x, y);
 
     let lives = POSES.iter().fold(0, |a, (offset_x, offset_y)| {
@@ -151,11 +325,7 @@ pub fn next_live(world: &World, x: isize, y: isize) -> bool {
         }
     });
 
-    match lives {
-        3 => true,
-        2 => now,
-        _ => false
-    }
+    rule.next(now, lives)
 }
 
 #[test]
@@ -173,6 +343,12 @@ fn test_new() {
     assert!(!world_a.is_live(3, 1));
 }
 
+#[test]
+fn test_new_without_columns() {
+    let g = Game::new(0, 3, &[true, true]);
+    assert_eq!(g.population(), 0);
+}
+
 #[test]
 fn test_step_blinker() {
     let blinker = vec![
@@ -211,7 +387,7 @@ fn test_step_farm() {
 
     let (game_wrapper, r) = g.step_farm(receiver);
 
-    r.recv().unwrap();
+    assert_eq!(r.recv().unwrap().0, 0);
     assert_eq!(game_wrapper.read().unwrap().lives(), vec![
         false, false, false,
         true, true, true,
@@ -219,7 +395,7 @@ fn test_step_farm() {
     ]);
 
     trigger.send(()).unwrap();
-    r.recv().unwrap();
+    assert_eq!(r.recv().unwrap().0, 1);
     assert_eq!(game_wrapper.read().unwrap().lives(), vec![
         false, true, false,
         false, true, false,
@@ -235,6 +411,166 @@ fn test_step_farm() {
     ]);
 }
 
+#[test]
+fn test_step_back() {
+    let glider = vec![
+        false, true, false, false, false,
+        false, false, true, false, false,
+        true, true, true, false, false,
+        false, false, false, false, false,
+        false, false, false, false, false,
+    ];
+    let mut g = Game::new(5, 5, &glider);
+    g.set_history_depth(2);
+
+    assert!(!g.step_back());
+
+    g.step();
+    let first = g.lives();
+    g.step();
+    g.step();
+    let third = g.lives();
+    assert_eq!(g.generation(), 3);
+
+    assert!(g.step_back());
+    assert!(g.step_back());
+    assert_eq!(g.generation(), 1);
+    assert_eq!(g.lives(), first);
+    assert!(!g.step_back());
+
+    g.step();
+    g.step();
+    assert_eq!(g.generation(), 3);
+    assert_eq!(g.lives(), third);
+}
+
+#[test]
+fn test_jump_to() {
+    let blinker = vec![
+        false, false, false,
+        true, true, true,
+        false, false, false,
+    ];
+    let mut g = Game::new(3, 3, &blinker);
+    g.set_history_depth(10);
+
+    for _ in 0..5 {
+        g.step();
+    }
+
+    assert!(g.jump_to(2));
+    assert_eq!(g.generation(), 2);
+    assert_eq!(g.lives(), blinker);
+
+    assert!(g.jump_to(5));
+    assert_eq!(g.generation(), 5);
+    assert!(!g.jump_to(6));
+    assert_eq!(g.generation(), 5);
+}
+
+#[test]
+fn test_jump_to_without_history() {
+    let blinker = vec![
+        false, false, false,
+        true, true, true,
+        false, false, false,
+    ];
+    let mut g = Game::new(3, 3, &blinker);
+    g.set_history_depth(0);
+
+    for _ in 0..5 {
+        g.step();
+    }
+
+    assert!(!g.jump_to(2));
+    assert!(!g.jump_to(0));
+    assert_eq!(g.generation(), 5);
+    assert!(g.jump_to(5));
+}
+
+#[test]
+fn test_jump_to_evicted() {
+    let blinker = vec![
+        false, false, false,
+        true, true, true,
+        false, false, false,
+    ];
+    let mut g = Game::new(3, 3, &blinker);
+    g.set_history_depth(2);
+
+    for _ in 0..5 {
+        g.step();
+    }
+
+    assert!(!g.jump_to(2));
+    assert_eq!(g.generation(), 5);
+    assert!(g.jump_to(4));
+    assert_eq!(g.generation(), 4);
+    assert_eq!(g.lives(), blinker);
+    assert!(g.jump_to(3));
+    assert!(!g.jump_to(2));
+    assert_eq!(g.generation(), 3);
+}
+
+#[test]
+fn test_step_n() {
+    let blinker = vec![
+        false, false, false,
+        true, true, true,
+        false, false, false,
+    ];
+    let mut g = Game::new(3, 3, &blinker);
+
+    g.step_n(3);
+    assert_eq!(g.generation(), 3);
+    assert_eq!(g.population(), 3);
+    assert_eq!(g.lives(), vec![
+        false, true, false,
+        false, true, false,
+        false, true, false,
+    ]);
+}
+
+#[test]
+fn test_run_until() {
+    let pair = vec![
+        true, true, false,
+        false, false, false,
+        false, false, false,
+    ];
+    let mut g = Game::new(3, 3, &pair);
+
+    assert_eq!(g.run_until(|g| g.population() < 2, 10), Some(1));
+    assert_eq!(g.run_until(|g| g.population() > 0, 10), None);
+    assert_eq!(g.generation(), 11);
+}
+
+#[test]
+fn test_track_ages() {
+    let blinker = vec![
+        false, false, false,
+        true, true, true,
+        false, false, false,
+    ];
+    let mut g = Game::new(3, 3, &blinker);
+    g.track_ages(3);
+
+    g.step();
+    g.step();
+
+    let ages = g.ages().unwrap();
+    assert_eq!(ages.ages(), &[
+        0, 0, 0,
+        1, 3, 1,
+        0, 0, 0,
+    ]);
+    assert_eq!(ages.trails(), &[
+        0, 3, 0,
+        0, 0, 0,
+        0, 3, 0,
+    ]);
+}
+
 #[test]
 fn test_swap() {
     let v = vec![true, true, false, false, true, false];
@@ -256,6 +592,7 @@ fn test_swap() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_next_live() {
     let v = vec![
         true, true, true, false, false, false, false, false, false, false,
@@ -273,12 +610,66 @@ fn test_next_live() {
     let w = Game::new(10, 10, &v);
 
     let world_a = &w.world_a.read().unwrap();
-    assert_eq!(next_live(world_a, 3, 3), true);
-    assert_eq!(next_live(world_a, 2, 3), false);
-    assert_eq!(next_live(world_a, 6, 4), false);
-    assert_eq!(next_live(world_a, 6, 5), false);
-    assert_eq!(next_live(world_a, 0, 7), true);
-    assert_eq!(next_live(world_a, 0, 8), true);
-    assert_eq!(next_live(world_a, 1, 7), true);
-    assert_eq!(next_live(world_a, 1, 8), true);
+    let rule = &Rule::default();
+    assert_eq!(next_live(world_a, rule, 3, 3), true);
+    assert_eq!(next_live(world_a, rule, 2, 3), false);
+    assert_eq!(next_live(world_a, rule, 6, 4), false);
+    assert_eq!(next_live(world_a, rule, 6, 5), false);
+    assert_eq!(next_live(world_a, rule, 0, 7), true);
+    assert_eq!(next_live(world_a, rule, 0, 8), true);
+    assert_eq!(next_live(world_a, rule, 1, 7), true);
+    assert_eq!(next_live(world_a, rule, 1, 8), true);
+}
+
+#[test]
+fn test_step_with_rule() {
+    use std::str::FromStr;
+
+    let pair = vec![
+        false, false, false, false,
+        false, true, true, false,
+        false, false, false, false,
+    ];
+    let mut g = Game::new(4, 3, &pair);
+    g.set_rule(Rule::from_str("B2/S").unwrap());
+
+    g.step();
+    assert_eq!(g.lives(), vec![
+        false, true, true, false,
+        false, false, false, false,
+        false, true, true, false,
+    ]);
+}
+
+#[test]
+fn test_step_farm_threads() {
+    let glider = vec![
+        false, true, false, false, false,
+        false, false, true, false, false,
+        true, true, true, false, false,
+    ];
+    let mut serial = Game::new(5, 7, &glider);
+    let mut farmed = Game::new(5, 7, &glider);
+    farmed.set_threads(3);
+
+    let (trigger, receiver) = channel();
+    let (_, r) = farmed.step_farm(receiver);
+
+    for _ in 0..8 {
+        assert_eq!(r.recv().unwrap(), (serial.generation(), serial.lives()));
+        serial.step();
+        trigger.send(()).unwrap();
+    }
+}
+
+#[test]
+fn test_cells() {
+    let mut g = Game::new(4, 4, &[]);
+    g.set_history_depth(2);
+    g.step();
+
+    g.set_cells(&[(0, 0), (2, 1), (3, 3), (9, 9)], true);
+    assert_eq!(g.live_cells().collect::<Vec<_>>(), vec![(0, 0), (2, 1), (3, 3)]);
+    assert_eq!(g.live_cells_in(Rect { x: 1, y: -1, width: 3, height: 3 }).collect::<Vec<_>>(), vec![(2, 1)]);
+    assert!(!g.step_back());
 }
@@ -0,0 +1,106 @@
+use crate::standard_error::StandardError;
+
+use std::str::FromStr;
+
+/// How cells are drawn as terminal text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyphs {
+    /// `■`/`□` and a space per cell.
+    Squares,
+    /// A column of 2 cells per character.
+    HalfBlocks,
+    /// 2 x 4 cells per braille character.
+    Braille,
+}
+
+/// Accepts "squares", "half" and "braille".
+impl FromStr for Glyphs {
+    type Err = StandardError;
+
+    fn from_str(s: &str) -> Result<Glyphs, StandardError> {
+        match s {
+            "squares" => Ok(Glyphs::Squares),
+            "half" => Ok(Glyphs::HalfBlocks),
+            "braille" => Ok(Glyphs::Braille),
+            _ => Err(StandardError::new(&format!("invalid glyphs: {:?}", s)))
+        }
+    }
+}
+
+impl Glyphs {
+    /// The next mode, each fitting more cells on screen than the last.
+    pub fn next(self) -> Glyphs {
+        match self {
+            Glyphs::Squares => Glyphs::HalfBlocks,
+            Glyphs::HalfBlocks => Glyphs::Braille,
+            Glyphs::Braille => Glyphs::Squares,
+        }
+    }
+
+    /// Cells that fit in `cols` x `rows` characters.
+    pub fn fit(self, cols: usize, rows: usize) -> (usize, usize) {
+        match self {
+            Glyphs::Squares => (cols / 2, rows),
+            Glyphs::HalfBlocks => (cols, rows * 2),
+            Glyphs::Braille => (cols * 2, rows * 4),
+        }
+    }
+
+    /// Text lines for row-major `cells` of a `width` x `height` field.
+    pub fn lines(self, width: usize, height: usize, cells: &[bool]) -> Vec<String> {
+        let live = |x: usize, y: usize| x < width && y < height && cells[y * width + x];
+
+        match self {
+            Glyphs::Squares => (0..height).map(|y| {
+                (0..width).map(|x| if live(x, y) { "■ " } else { "□ " }).collect()
+            }).collect(),
+            Glyphs::HalfBlocks => (0..height).step_by(2).map(|y| {
+                (0..width).map(|x| match (live(x, y), live(x, y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                }).collect()
+            }).collect(),
+            Glyphs::Braille => (0..height).step_by(4).map(|y| {
+                (0..width).step_by(2).map(|x| {
+                    let dots = BRAILLE_DOTS.iter()
+                        .filter(|(dx, dy, _)| live(x + dx, y + dy))
+                        .fold(0, |bits, (_, _, bit)| bits | bit);
+                    std::char::from_u32(0x2800 + dots).unwrap()
+                }).collect()
+            }).collect(),
+        }
+    }
+}
+
+/// Offset within the 2 x 4 block and Unicode bit of each braille dot.
+const BRAILLE_DOTS: [(usize, usize, u32); 8] = [
+    (0, 0, 0x01), (0, 1, 0x02), (0, 2, 0x04), (1, 0, 0x08),
+    (1, 1, 0x10), (1, 2, 0x20), (0, 3, 0x40), (1, 3, 0x80),
+];
+
+#[test]
+fn test_from_str() {
+    assert_eq!(Glyphs::from_str("half").unwrap(), Glyphs::HalfBlocks);
+    assert!(Glyphs::from_str("blocks").is_err());
+}
+
+#[test]
+fn test_lines() {
+    let cells = vec![
+        true, false, true,
+        false, false, true,
+        true, true, false,
+    ];
+
+    assert_eq!(Glyphs::Squares.lines(3, 3, &cells), vec!["■ □ ■ ", "□ □ ■ ", "■ ■ □ "]);
+    assert_eq!(Glyphs::HalfBlocks.lines(3, 3, &cells), vec!["▀ █", "▀▀ "]);
+    assert_eq!(Glyphs::Braille.lines(3, 3, &cells), vec!["\u{2825}\u{2803}"]);
+}
+
+#[test]
+fn test_fit() {
+    assert_eq!(Glyphs::Squares.fit(80, 24), (40, 24));
+    assert_eq!(Glyphs::Braille.fit(80, 24), (160, 96));
+}
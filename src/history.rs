@@ -0,0 +1,100 @@
+use std::collections::VecDeque;
+
+/// Bounded record of recent generations.
+///
+/// Each entry keeps only the indices of the cells that flipped between two
+/// consecutive generations, so rewinding and replaying is a matter of toggling
+/// those cells again.
+#[derive(Debug, Clone)]
+pub struct History {
+    depth: usize,
+    first: usize,
+    deltas: VecDeque<Vec<usize>>,
+}
+
+impl History {
+    pub fn new(depth: usize, generation: usize) -> History {
+        History {
+            depth,
+            first: generation,
+            deltas: VecDeque::with_capacity(depth),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn newest(&self) -> usize {
+        self.first + self.deltas.len()
+    }
+
+    /// Records the cells that flipped between `from` and `from + 1`.
+    /// Anything recorded after `from` is dropped since it belongs to another timeline.
+    pub fn record(&mut self, from: usize, delta: Vec<usize>) {
+        if self.depth == 0 {
+            return;
+        }
+
+        if from < self.first || from > self.newest() {
+            self.deltas.clear();
+            self.first = from;
+        } else {
+            self.deltas.truncate(from - self.first);
+        }
+
+        self.deltas.push_back(delta);
+
+        while self.deltas.len() > self.depth {
+            self.deltas.pop_front();
+            self.first += 1;
+        }
+    }
+
+    /// Returns the cells that flipped between `from` and `from + 1`, if retained.
+    pub fn delta(&self, from: usize) -> Option<&Vec<usize>> {
+        if from < self.first {
+            return None;
+        }
+
+        self.deltas.get(from - self.first)
+    }
+}
+
+#[test]
+fn test_record() {
+    let mut h = History::new(2, 0);
+
+    h.record(0, vec![1]);
+    h.record(1, vec![2]);
+    assert_eq!((h.first, h.newest()), (0, 2));
+
+    h.record(2, vec![3]);
+    assert_eq!((h.first, h.newest()), (1, 3));
+    assert_eq!(h.delta(0), None);
+    assert_eq!(h.delta(1), Some(&vec![2]));
+    assert_eq!(h.delta(2), Some(&vec![3]));
+    assert_eq!(h.delta(3), None);
+}
+
+#[test]
+fn test_record_truncates_future() {
+    let mut h = History::new(4, 0);
+
+    h.record(0, vec![1]);
+    h.record(1, vec![2]);
+    h.record(2, vec![3]);
+    h.record(1, vec![9]);
+
+    assert_eq!((h.first, h.newest()), (0, 2));
+    assert_eq!(h.delta(1), Some(&vec![9]));
+}
+
+#[test]
+fn test_record_disabled() {
+    let mut h = History::new(0, 0);
+
+    h.record(0, vec![1]);
+    assert_eq!((h.first, h.newest()), (0, 0));
+    assert_eq!(h.delta(0), None);
+}
@@ -0,0 +1,55 @@
+use crate::render::Style;
+
+use png::{BitDepth, ColorType, Encoder};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Writes palette-indexed `cells` of a `width` x `height` field as a PNG drawn with `style`.
+pub fn write_png<W: Write>(w: W, width: usize, height: usize, cells: &[u8], style: &Style) -> Result<(), Box<dyn Error>> {
+    let (pixel_width, pixel_height) = style.size(width, height);
+
+    let mut encoder = Encoder::new(w, pixel_width as u32, pixel_height as u32);
+    encoder.set_color(ColorType::Indexed);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.set_palette(style.palette());
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&style.rasterize_indexed(width, height, cells))?;
+
+    Ok(())
+}
+
+pub fn save_png(filename: &str, width: usize, height: usize, cells: &[u8], style: &Style) -> Result<(), Box<dyn Error>> {
+    let mut w = BufWriter::new(File::create(filename)?);
+    write_png(&mut w, width, height, cells, style)?;
+    w.flush()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_write_png() {
+    use crate::render::Color;
+
+    let style = Style { scale: 2, border: 1, ..Style::default() };
+    let mut buf = vec![];
+    write_png(&mut buf, 2, 1, &[1, 0], &style).unwrap();
+
+    let decoder = png::Decoder::new(buf.as_slice());
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+
+    assert_eq!((info.width, info.height), (6, 4));
+    assert_eq!(info.color_type, ColorType::Indexed);
+    assert_eq!(&pixels[..info.buffer_size()], &[
+        3, 3, 3, 3, 3, 3,
+        3, 1, 1, 0, 0, 3,
+        3, 1, 1, 0, 0, 3,
+        3, 3, 3, 3, 3, 3,
+    ]);
+
+    let palette = reader.info().palette.as_ref().unwrap();
+    assert_eq!(&palette[3..6], &[Color::BLACK.0, Color::BLACK.1, Color::BLACK.2]);
+}
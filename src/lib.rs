@@ -1,4 +1,40 @@
-pub mod world;
+//! Conway's Game of Life and its relatives, with pattern IO and rendering.
+//!
+//! `Universe` loads a pattern, steps it and renders it as an image or animation;
+//! the types re-exported next to it and the modules below it are there for finer control.
+//! The terminal viewer used by the `lifegame` binary needs the default `cli` feature.
+
+mod world;
+mod history;
 pub mod rle;
 pub mod game;
 pub mod standard_error;
+pub mod predicate;
+pub mod snapshot;
+pub mod engine;
+pub mod rule;
+pub mod render;
+pub mod viewport;
+pub mod age;
+pub mod image;
+pub mod animation;
+pub mod svg;
+#[cfg(feature = "cli")]
+pub mod tui;
+#[cfg(feature = "cli")]
+pub mod glyph;
+pub mod analysis;
+pub mod pattern;
+pub mod universe;
+
+pub use crate::animation::{Animation, AnimationWriter, Format};
+pub use crate::engine::{LifeEngine, Rect};
+pub use crate::game::Game;
+pub use crate::pattern::Pattern;
+pub use crate::render::{Color, Heat, Style};
+pub use crate::rle::Rle;
+pub use crate::rule::Rule;
+pub use crate::snapshot::Snapshot;
+pub use crate::standard_error::StandardError;
+pub use crate::universe::{Recording, Universe};
+pub use crate::viewport::Framing;
@@ -1,126 +1,596 @@
-#![feature(test)]
-extern crate test;
+#[macro_use]
+extern crate clap;
 
-use crate::rle::Rle;
-use crate::game::Game;
+use lifegame::rle::Rle;
+use lifegame::game::Game;
+use lifegame::snapshot::Snapshot;
+use lifegame::engine::LifeEngine;
+use lifegame::rule::Rule;
+use lifegame::render::{Color, Heat, Style, BACKGROUND};
+use lifegame::viewport::{Framing, Viewport};
+use lifegame::image::save_png;
+use lifegame::svg::save_svg;
+use lifegame::tui::Tui;
+use lifegame::glyph::Glyphs;
+use lifegame::universe::Universe;
+use lifegame::analysis::{bounding_box, fate, Fate, Period, Tracker};
+use lifegame::animation::{Animation, Format};
+use lifegame::standard_error::StandardError;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use std::str::FromStr;
-use std::io::Write;
+use std::io::{self, Read, Write};
+use crossterm::tty::IsTty;
 use std::{thread, time};
-use gif::{Frame, Encoder, Repeat, SetParameter};
-use std::fs::File;
-use std::borrow::Cow;
-use std::mem;
+use std::fs;
+use std::error::Error;
 use std::sync::mpsc::channel;
 
-mod world;
-mod rle;
-mod game;
-mod standard_error;
-mod benchmark;
-
-/// Usage: lifegame gif      INPUT MARGIN DELAY TURNS OUTPUT
-/// Usage: lifegame terminal INPUT MARGIN DELAY
 fn main() {
-    let args: Vec<String> = std::env::args().skip(1).collect();
+    let matches = app().get_matches();
+
+    let result = match matches.subcommand() {
+        ("gif", Some(m)) => gif(m),
+        ("png", Some(m)) => png(m),
+        ("term", Some(m)) => term(m),
+        ("run", Some(m)) => run(m),
+        ("info", Some(m)) => info(m),
+        ("convert", Some(m)) => convert(m),
+        _ => unreachable!(),
+    };
 
-    if args.is_empty() {
-        write_usage_and_exit()
+    if let Err(e) = result {
+        eprintln!("lifegame: {}", e);
+        std::process::exit(1);
     }
+}
 
-    let mode = (&args[0]).as_str();
+fn app() -> App<'static, 'static> {
+    let input = Arg::with_name("INPUT")
+        .help("RLE pattern, or a checkpoint written by `gif --checkpoint`; - reads stdin")
+        .required_unless("rle");
+    let inline = Arg::with_name("rle").long("rle").takes_value(true).conflicts_with("INPUT")
+        .help("Pattern given in place of INPUT, such as '2o$obo$o!'; the header line may be left out");
+    let margin = Arg::with_name("margin").long("margin").takes_value(true).default_value("0")
+        .help("Dead cells added around the pattern");
+    let delay = Arg::with_name("delay").long("delay").takes_value(true).default_value("100")
+        .help("Milliseconds per generation");
+    let turns = Arg::with_name("turns").long("turns").visible_alias("gens").takes_value(true).default_value("100")
+        .help("Generation to stop at");
+    let threads = Arg::with_name("threads").long("threads").takes_value(true).default_value("1")
+        .help("Workers to split each generation across, 0 for one per CPU");
+    let rule = Arg::with_name("rule").long("rule").takes_value(true)
+        .help("Rule such as B3/S23, overriding the one in INPUT");
+    let view = Arg::with_name("view").long("view").takes_value(true).default_value("full")
+        .help("Area to show: full, fit[:PADDING], follow:WxH or X,Y:WxH");
 
-    match mode {
-        "gif" | "gif_p" | "gif_pp" if args.len() <= 6 => (),
-        "term" if args.len() <= 4 => (),
-        _ => write_usage_and_exit()
-    }
-    let is_gif = mode != "term";
-    let margin = usize::from_str(&args[2]).expect("invalid MARGIN");
-    let delay = u16::from_str(&args[3]).expect("invalid DELAY");
+    App::new("lifegame")
+        .version(crate_version!())
+        .about("Lifegame animation gif generator")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .setting(AppSettings::VersionlessSubcommands)
+        .subcommand(SubCommand::with_name("gif")
+            .about("Renders generations into an animated GIF, APNG or WebP")
+            .args(&[input.clone(), inline.clone(), margin.clone(), delay.clone(), turns.clone(), threads.clone(), rule.clone(), view.clone()])
+            .arg(Arg::with_name("output").long("output").short("o").takes_value(true).required(true)
+                .help("File to write, whose extension picks the format: .gif, .png or .webp"))
+            .arg(Arg::with_name("checkpoint").long("checkpoint").takes_value(true)
+                .help("Saves the game to this file every 1000 generations; resuming from it continues a .gif OUTPUT, \
+                       while .png and .webp only get the generations from the checkpoint on; \
+                       not allowed with --view fit or follow or with --age"))
+            .arg(Arg::with_name("every").long("every").takes_value(true).default_value("1")
+                .help("Renders only every Nth generation"))
+            .args(&style_args()))
+        .subcommand(SubCommand::with_name("png")
+            .about("Renders a generation, or a numbered sequence of them, as PNG or SVG")
+            .args(&[input.clone(), inline.clone(), margin.clone(), threads.clone(), rule.clone(), view.clone()])
+            .arg(Arg::with_name("output").long("output").short("o").takes_value(true).required(true)
+                .help("PNG or .svg file to write; with --to, a path where {} becomes the generation"))
+            .arg(Arg::with_name("from").long("from").takes_value(true).default_value("0")
+                .help("Generation to render"))
+            .arg(Arg::with_name("to").long("to").takes_value(true)
+                .help("Renders every generation from --from through this one"))
+            .arg(Arg::with_name("every").long("every").takes_value(true).default_value("1")
+                .help("Renders only every Nth generation of the sequence"))
+            .arg(Arg::with_name("labels").long("labels")
+                .help("Prints field coordinates along the edges of SVG output"))
+            .args(&style_args()))
+        .subcommand(SubCommand::with_name("term")
+            .about("Shows the pattern in an interactive terminal viewer")
+            .args(&[input.clone(), inline.clone(), margin.clone(), delay, rule.clone(), view])
+            .arg(Arg::with_name("glyphs").long("glyphs").takes_value(true).default_value("squares")
+                .possible_values(&["squares", "half", "braille"])
+                .help("Cells per character: squares (1), half blocks (1x2) or braille (2x4)"))
+            .arg(Arg::with_name("fps").long("fps").takes_value(true).default_value("30")
+                .help("Most screen updates per second, however short --delay is")))
+        .subcommand(SubCommand::with_name("run")
+            .about("Runs generations and writes the final pattern")
+            .args(&[input.clone(), inline.clone(), margin.clone(), turns, threads, rule.clone()])
+            .arg(Arg::with_name("output").long("output").short("o").takes_value(true)
+                .help("File to write instead of stdout"))
+            .arg(Arg::with_name("format").long("format").takes_value(true).possible_values(&["rle", "snapshot"])
+                .help("Output format, by default snapshot for a .lgsn OUTPUT and RLE otherwise"))
+            .arg(Arg::with_name("summary").long("summary").takes_value(true)
+                .help("Writes population, bounding box, period and elapsed time as JSON to this file, - for stdout")))
+        .subcommand(SubCommand::with_name("info")
+            .about("Prints the metadata, size, population and rule of a pattern")
+            .args(&[input.clone(), inline.clone(), rule.clone()])
+            .arg(Arg::with_name("analyze").long("analyze").takes_value(true)
+                .help("Runs up to this many generations to tell still lifes, oscillators and spaceships apart")))
+        .subcommand(SubCommand::with_name("convert")
+            .about("Reshapes a pattern or checkpoint and writes it as RLE, a checkpoint or an image")
+            .args(&[input, inline, rule])
+            .arg(Arg::with_name("output").long("output").short("o").takes_value(true).required(true)
+                .help("File to write, whose extension picks the format: .rle, .lgsn (checkpoint), .png or .svg"))
+            .arg(Arg::with_name("crop").long("crop").takes_value(true)
+                .help("Keeps only the X,Y:WxH rectangle"))
+            .arg(Arg::with_name("trim").long("trim")
+                .help("Crops to the bounding box of the live cells"))
+            .arg(Arg::with_name("rotate").long("rotate").takes_value(true).possible_values(&["90", "180", "270"])
+                .help("Turns the pattern clockwise by this many degrees"))
+            .arg(Arg::with_name("flip").long("flip").takes_value(true).possible_values(&["h", "v"])
+                .help("Mirrors the pattern horizontally or vertically"))
+            .arg(Arg::with_name("pad").long("pad").alias("margin").takes_value(true)
+                .help("Dead cells added around the result"))
+            .args(&style_args()))
+}
+
+fn style_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("scale").long("scale").takes_value(true).default_value("1")
+            .help("Pixels per cell side"),
+        Arg::with_name("fg").long("fg").takes_value(true).default_value("000000")
+            .help("Live cell color as rrggbb"),
+        Arg::with_name("bg").long("bg").takes_value(true).default_value("ffffff")
+            .help("Dead cell color as rrggbb"),
+        Arg::with_name("grid").long("grid").takes_value(true)
+            .help("Draws 1 pixel grid lines between cells in this color"),
+        Arg::with_name("border").long("border").takes_value(true).default_value("0")
+            .help("Pixels of frame around the field"),
+        Arg::with_name("border-color").long("border-color").takes_value(true).default_value("808080")
+            .help("Frame color as rrggbb"),
+        Arg::with_name("age").long("age")
+            .help("Colors live cells by age and leaves fading trails behind dead ones"),
+        Arg::with_name("trail").long("trail").takes_value(true).default_value("8")
+            .help("Generations a dead cell takes to fade with --age"),
+        Arg::with_name("young").long("young").takes_value(true).default_value("ffd000")
+            .help("Color of newborn cells with --age"),
+        Arg::with_name("old").long("old").takes_value(true).default_value("c00000")
+            .help("Color of long-lived cells with --age"),
+        Arg::with_name("trail-color").long("trail-color").takes_value(true).default_value("6080ff")
+            .help("Color of freshly dead cells with --age"),
+    ]
+}
 
-    let (w, h, map) = Rle::from_file(&args[1], margin).expect("parse INPUT error");
-    let game = Game::new(w, h, &map);
+fn style(m: &ArgMatches) -> Result<Style, Box<dyn Error>> {
+    let scale = value_t!(m, "scale", usize).unwrap_or_else(|e| e.exit());
+    if scale == 0 {
+        return Err(Box::new(StandardError::new("--scale must be at least 1")));
+    }
 
-    if is_gif {
-        let turns = usize::from_str(&args[4]).expect("invalid TURNS");
-        let output = &args[5];
-        if mode == "gif_pp" {
-            animation_gif_p(game, delay, turns, output);
+    Ok(Style {
+        scale,
+        foreground: Color::from_str(m.value_of("fg").unwrap())?,
+        background: Color::from_str(m.value_of("bg").unwrap())?,
+        grid: match m.value_of("grid") {
+            Some(grid) => Some(Color::from_str(grid)?),
+            None => None
+        },
+        border: value_t!(m, "border", usize).unwrap_or_else(|e| e.exit()),
+        border_color: Color::from_str(m.value_of("border-color").unwrap())?,
+        heat: if m.is_present("age") {
+            Some(Heat {
+                young: Color::from_str(m.value_of("young").unwrap())?,
+                old: Color::from_str(m.value_of("old").unwrap())?,
+                trail: Color::from_str(m.value_of("trail-color").unwrap())?,
+            })
         } else {
-            animation_gif(game, delay, turns, output);
+            None
+        },
+    })
+}
+
+fn gif(m: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut game = load(m)?;
+    let style = style(m)?;
+    if style.heat.is_some() {
+        game.track_ages(value_t!(m, "trail", u8).unwrap_or_else(|e| e.exit()));
+    }
+    let framing = Framing::from_str(m.value_of("view").unwrap())?;
+    let delay = value_t!(m, "delay", u64).unwrap_or_else(|e| e.exit());
+    let turns = value_t!(m, "turns", usize).unwrap_or_else(|e| e.exit());
+    let threads = value_t!(m, "threads", usize).unwrap_or_else(|e| e.exit());
+    let every = value_t!(m, "every", usize).unwrap_or_else(|e| e.exit()).max(1);
+    let checkpoint = m.value_of("checkpoint");
+    // A checkpoint holds only the cells, so a resumed run could not rebuild the crop or the ages it had.
+    let moving = matches!(framing, Framing::Fit(_) | Framing::Follow(..));
+    if (checkpoint.is_some() || game.generation() > 0) && (moving || style.heat.is_some()) {
+        let message = "--view fit and follow and --age cannot be resumed from a checkpoint; use --view full or X,Y:WxH";
+        return Err(Box::new(StandardError::new(message)));
+    }
+    let output = m.value_of("output").unwrap();
+    let format = Format::from_path(output)?;
+
+    let (width, height) = (game.width(), game.height());
+    let mut viewport = Viewport::new(framing, width, height, &game.lives());
+    let rect = viewport.rect();
+    // Frames are taken every `every` generations counted from 0, so a resumed run lines up with the frames before it.
+    let written = game.generation().min(turns).div_ceil(every);
+
+    let animation = Animation {
+        width: rect.width,
+        height: rect.height,
+        delay,
+        frames: turns.div_ceil(every) - written,
+        style: style.clone(),
+    };
+    let mut writer = animation.resume(format, output, written)?;
+    let each = |game: &Game, lives: Vec<bool>| {
+        if let Some(path) = checkpoint {
+            if game.generation() % CHECKPOINT_EVERY == 0 || game.generation() >= turns {
+                writer.flush()?;
+                Snapshot::to_file(game, path)?;
+            }
         }
+        if game.generation() < turns && game.generation() % every == 0 {
+            viewport.update(width, &lives);
+            let cells = style.cells(&lives, game.ages());
+            writer.write_frame(&viewport.crop(width, height, &cells, BACKGROUND))?;
+        }
+        Ok(())
+    };
+
+    if threads == 1 {
+        simulate(game, turns, each)?;
     } else {
-        terminal(game, delay);
+        simulate_farm(game, turns, threads, each)?;
     }
+
+    writer.finish()
 }
 
-fn write_usage_and_exit() {
-    writeln!(std::io::stderr(), "Usage: lifegame gif  INPUT MARGIN DELAY TURNS OUTPUT").unwrap();
-    writeln!(std::io::stderr(), "       lifegame term INPUT MARGIN DELAY").unwrap();
-    std::process::exit(1);
+fn png(m: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut game = load(m)?;
+    let style = style(m)?;
+    if style.heat.is_some() {
+        game.track_ages(value_t!(m, "trail", u8).unwrap_or_else(|e| e.exit()));
+    }
+    let framing = Framing::from_str(m.value_of("view").unwrap())?;
+    let threads = value_t!(m, "threads", usize).unwrap_or_else(|e| e.exit());
+    let from = value_t!(m, "from", usize).unwrap_or_else(|e| e.exit());
+    let every = value_t!(m, "every", usize).unwrap_or_else(|e| e.exit()).max(1);
+    let output = m.value_of("output").unwrap();
+    let svg = output.to_lowercase().ends_with(".svg");
+    let labels = m.is_present("labels");
+    let to = match m.value_of("to") {
+        Some(_) => value_t!(m, "to", usize).unwrap_or_else(|e| e.exit()),
+        None => from
+    };
+    if to < from {
+        return Err(Box::new(StandardError::new(&format!("--to {} comes before --from {}", to, from))));
+    }
+    if game.generation() > from {
+        let message = format!("INPUT is already at generation {}, past --from {}", game.generation(), from);
+        return Err(Box::new(StandardError::new(&message)));
+    }
+    if to > from && !output.contains("{}") {
+        return Err(Box::new(StandardError::new("--output needs a {} for the generation when rendering a sequence")));
+    }
+    let digits = to.to_string().len();
+
+    let (width, height) = (game.width(), game.height());
+    let mut viewport = Viewport::new(framing, width, height, &game.lives());
+    let rect = viewport.rect();
+
+    let each = |game: &Game, lives: Vec<bool>| {
+        let generation = game.generation();
+        viewport.update(width, &lives);
+        if generation >= from && (generation - from) % every == 0 {
+            let cells = style.cells(&lives, game.ages());
+            let path = output.replace("{}", &format!("{:0width$}", generation, width = digits));
+            let cropped = viewport.crop(width, height, &cells, BACKGROUND);
+            if svg {
+                save_svg(&path, &viewport.rect(), &cropped, &style, labels)?;
+            } else {
+                save_png(&path, rect.width, rect.height, &cropped, &style)?;
+            }
+        }
+        Ok(())
+    };
+
+    if threads == 1 {
+        simulate(game, to, each)
+    } else {
+        simulate_farm(game, to, threads, each)
+    }
 }
 
-fn animation_gif_p(game: Game, delay: u16, turns: usize, output: &String) {
-    let mut encoder = prepare(&game, delay, output);
-    let (trigger_sender, trigger_receiver) = channel();
-    let (_game_wrapper, result_receiver) = game.step_farm(trigger_receiver);
+fn term(m: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let game = load(m)?;
+    let delay = value_t!(m, "delay", u64).unwrap_or_else(|e| e.exit());
+    let framing = Framing::from_str(m.value_of("view").unwrap())?;
+    let glyphs = Glyphs::from_str(m.value_of("glyphs").unwrap())?;
+    let fps = value_t!(m, "fps", u64).unwrap_or_else(|e| e.exit());
 
-    for _ in 0..turns {
-        let lives = result_receiver.recv().unwrap();
-        trigger_sender.send(()).unwrap();
-        encoder(lives);
+    if io::stdout().is_tty() && io::stdin().is_tty() {
+        // A pattern piped in on stdin is gone once read, so `r` can only say so.
+        let from_stdin = m.value_of("rle").is_none() && m.value_of("INPUT") == Some("-");
+        let reload = || -> Result<Game, Box<dyn Error>> {
+            if from_stdin {
+                return Err(Box::new(StandardError::new("stdin cannot be read again")));
+            }
+            load(m)
+        };
+        let mut tui = Tui::new(game, framing, glyphs, delay, reload);
+        tui.set_fps(fps);
+        tui.run()
+    } else {
+        terminal(game, delay, framing, glyphs)
     }
 }
 
-fn animation_gif(mut game: Game, delay: u16, turns: usize, output: &String) {
-    let mut encoder = prepare(&game, delay, output);
+fn run(m: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let game = load(m)?;
+    let turns = value_t!(m, "turns", usize).unwrap_or_else(|e| e.exit());
+    let threads = value_t!(m, "threads", usize).unwrap_or_else(|e| e.exit());
+    let output = m.value_of("output");
+    let summary = m.value_of("summary");
+    let snapshot = match m.value_of("format") {
+        Some(format) => format == "snapshot",
+        None => output.is_some_and(|path| path.to_lowercase().ends_with(".lgsn")),
+    };
 
-    for _ in 0..turns {
-        encoder(game.lives());
-        game.step();
+    let started = time::Instant::now();
+    let mut tracker = Tracker::new();
+    let mut last = vec![];
+    let mut report = String::new();
+    let each = |game: &Game, lives: Vec<bool>| {
+        if summary.is_some() {
+            tracker.observe(game.generation(), game.width(), &lives);
+        }
+        if game.generation() >= turns {
+            if snapshot {
+                Snapshot::write(game, &mut last)?;
+            } else {
+                last = Rle::encode(game.width(), game.height(), &lives, &game.rule()).into_bytes();
+            }
+            report = summarize(game.generation(), game.width(), &lives, tracker.period(), started.elapsed());
+        }
+        Ok(())
+    };
+
+    if threads == 1 {
+        simulate(game, turns, each)?;
+    } else {
+        simulate_farm(game, turns, threads, each)?;
     }
+
+    match output {
+        Some(path) => fs::write(path, last)?,
+        None => io::stdout().write_all(&last)?,
+    }
+    match summary {
+        Some("-") => println!("{}", report),
+        Some(path) => fs::write(path, report + "\n")?,
+        None => {}
+    }
+    Ok(())
 }
 
-fn prepare(game: &Game, delay: u16, output: &String) -> Box<FnMut(Vec<bool>) -> ()> {
-    let color_map = &[0xFF, 0xFF, 0xFF, 0, 0, 0];
-    let (width, height) = (game.width as u16, game.height as u16);
+/// JSON object describing the final generation of `run`.
+fn summarize(generation: usize, width: usize, lives: &[bool], period: Option<Period>, elapsed: time::Duration) -> String {
+    let bbox = match bounding_box(width, lives) {
+        Some(b) => format!(r#"{{"x": {}, "y": {}, "width": {}, "height": {}}}"#, b.x, b.y, b.width, b.height),
+        None => "null".to_string(),
+    };
+    let (period, displacement) = match period {
+        Some(p) => (p.period.to_string(), format!("[{}, {}]", p.dx, p.dy)),
+        None => ("null".to_string(), "null".to_string()),
+    };
+    let population = lives.iter().filter(|doa| **doa).count();
 
-    let image = File::create(output).unwrap();
-    let mut encoder = Encoder::new(image, width, height, color_map).unwrap();
-    encoder.set(Repeat::Infinite).unwrap();
+    format!(r#"{{"generation": {}, "population": {}, "bbox": {}, "period": {}, "displacement": {}, "extinct": {}, "elapsed": {:.3}}}"#,
+            generation, population, bbox, period, displacement, population == 0, elapsed.as_secs_f64())
+}
 
-    Box::new(move |lives: Vec<bool>| {
-        let state = unsafe{
-            mem::transmute::<Vec<bool>, Vec<u8>>(lives)
-        };
-        let mut frame = Frame::default();
-        frame.delay = delay;
-        frame.width = width;
-        frame.height = height;
-        frame.buffer = Cow::Borrowed(&*state);
+fn info(m: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let (input, bytes) = read_input(m)?;
+    let game = parse(m, &input, bytes.clone())?;
 
-        encoder.write_frame(&frame).unwrap()
-    })
+    if !Snapshot::matches(&bytes) {
+        let raw = Rle::with_header(&String::from_utf8(bytes)?);
+        let header = Rle::header(&raw)?;
+        if let Some(name) = &header.name {
+            println!("name:       {}", name);
+        }
+        if let Some(author) = &header.author {
+            println!("author:     {}", author);
+        }
+        for comment in &header.comments {
+            println!("comment:    {}", comment);
+        }
+
+        let cells = Rle::live_cells(&raw)?;
+        println!("declared:   {} x {}", header.width, header.height);
+        match (cells.iter().map(|c| c.0).max(), cells.iter().map(|c| c.1).max()) {
+            (Some(right), Some(bottom)) if right as usize >= header.width || bottom as usize >= header.height => {
+                println!("warning:    data reaches {} x {}, cells outside the declared size are dropped", right + 1, bottom + 1);
+            }
+            _ => {}
+        }
+    }
+
+    println!("size:       {} x {}", game.width(), game.height());
+    match game.bounding_box() {
+        Some(b) => println!("bbox:       {} x {} at {},{}", b.width, b.height, b.x, b.y),
+        None => println!("bbox:       none"),
+    }
+    println!("population: {}", game.population());
+    println!("rule:       {}", game.rule());
+    if game.generation() > 0 {
+        println!("generation: {}", game.generation());
+    }
+
+    if m.is_present("analyze") {
+        let limit = value_t!(m, "analyze", usize).unwrap_or_else(|e| e.exit());
+        match fate(&game, limit) {
+            Fate::Dies(after) => println!("fate:       dies out after {} generations", after),
+            Fate::Repeats(after, p) if p.moves() => {
+                println!("fate:       {} with period {} moving {},{} (first seen again after {} generations)",
+                         p.kind(), p.period, p.dx, p.dy, after)
+            }
+            Fate::Repeats(after, p) => {
+                println!("fate:       {} with period {} (first seen again after {} generations)", p.kind(), p.period, after)
+            }
+            Fate::Unknown => println!("fate:       no repeat within {} generations", limit),
+        }
+    }
+    Ok(())
+}
+
+/// Applies --crop, --trim, --rotate, --flip and --pad in that order.
+fn convert(m: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut universe = Universe::new(load(m)?);
+    let output = m.value_of("output").unwrap();
+    let mut pattern = universe.pattern();
+
+    if let Some(crop) = m.value_of("crop") {
+        match Framing::from_str(crop)? {
+            Framing::Fixed(rect) => pattern = pattern.crop(rect),
+            _ => return Err(Box::new(StandardError::new(&format!("invalid crop: {:?}", crop)))),
+        }
+    }
+    if m.is_present("trim") {
+        pattern = pattern.trim();
+    }
+    if m.is_present("rotate") {
+        pattern = pattern.rotate(value_t!(m, "rotate", usize).unwrap_or_else(|e| e.exit()) / 90);
+    }
+    match m.value_of("flip") {
+        Some("h") => pattern = pattern.flip_horizontal(),
+        Some("v") => pattern = pattern.flip_vertical(),
+        _ => {}
+    }
+    if m.is_present("pad") {
+        pattern = pattern.pad(value_t!(m, "pad", usize).unwrap_or_else(|e| e.exit()));
+    }
+    if pattern.width == 0 || pattern.height == 0 {
+        return Err(Box::new(StandardError::new("nothing left to write")));
+    }
+    universe.set_pattern(pattern);
+
+    let extension = output.rsplit('.').next().unwrap_or("").to_lowercase();
+    // Every option is checked before OUTPUT is created, so a bad one leaves no empty file behind.
+    let style = style(m)?;
+    let mut w = io::BufWriter::new(fs::File::create(output)?);
+    match extension.as_str() {
+        "lgsn" => universe.write_snapshot(&mut w)?,
+        "png" => universe.render_png(&mut w, &style)?,
+        "svg" => universe.render_svg(&mut w, &style)?,
+        _ => w.write_all(universe.to_rle().as_bytes())?,
+    }
+    Ok(w.flush()?)
 }
 
-fn terminal(mut game: Game, delay: u16) {
-    let h = game.height as usize;
-    let w = game.width as usize;
-    let wait = time::Duration::from_millis(delay as u64);
+/// Contents of INPUT, stdin for "-" or `--rle`, along with a name for messages.
+fn read_input(m: &ArgMatches) -> Result<(String, Vec<u8>), Box<dyn Error>> {
+    if let Some(inline) = m.value_of("rle") {
+        return Ok(("--rle".to_string(), inline.as_bytes().to_vec()));
+    }
+
+    let input = m.value_of("INPUT").unwrap();
+    let mut bytes = vec![];
+    if input == "-" {
+        io::stdin().read_to_end(&mut bytes)?;
+        Ok(("stdin".to_string(), bytes))
+    } else {
+        bytes = fs::read(input).map_err(|e| StandardError::new(&format!("{}: {}", input, e)))?;
+        Ok((input.to_string(), bytes))
+    }
+}
+
+/// Reads INPUT as a checkpoint or an RLE pattern, applying `--margin` and `--rule` when given.
+fn load(m: &ArgMatches) -> Result<Game, Box<dyn Error>> {
+    let (input, bytes) = read_input(m)?;
+    parse(m, &input, bytes)
+}
+
+/// Same as `load` for contents already read from `input`.
+fn parse(m: &ArgMatches, input: &str, bytes: Vec<u8>) -> Result<Game, Box<dyn Error>> {
+    let margin = match m.value_of("margin") {
+        Some(_) => value_t!(m, "margin", usize).unwrap_or_else(|e| e.exit()),
+        None => 0
+    };
+    let rule = match m.value_of("rule") {
+        Some(rule) => Some(Rule::from_str(rule)?),
+        None => None
+    };
+
+    let mut game = Universe::from_bytes(&bytes, margin)
+        .map_err(|e| StandardError::new(&format!("{}: {}", input, e)))?
+        .into_game();
+
+    if let Some(rule) = rule {
+        game.set_rule(rule);
+    }
+
+    Ok(game)
+}
+
+const CHECKPOINT_EVERY: usize = 1000;
+
+/// Calls `each` with every generation from the current one through `turns`.
+fn simulate<E, F>(mut game: E, turns: usize, mut each: F) -> Result<(), Box<dyn Error>>
+    where E: LifeEngine, F: FnMut(&E, Vec<bool>) -> Result<(), Box<dyn Error>> {
+    loop {
+        let lives = game.lives();
+        each(&game, lives)?;
+        if game.generation() >= turns {
+            return Ok(());
+        }
+        game.step();
+    }
+}
+
+/// Same as `simulate`, with each generation split across `threads` workers.
+fn simulate_farm<F>(mut game: Game, turns: usize, threads: usize, mut each: F) -> Result<(), Box<dyn Error>>
+    where F: FnMut(&Game, Vec<bool>) -> Result<(), Box<dyn Error>> {
+    game.set_threads(if threads == 0 { num_cpus::get() } else { threads });
+
+    let (trigger_sender, trigger_receiver) = channel();
+    let (game_wrapper, result_receiver) = game.step_farm(trigger_receiver);
+
+    while let Ok((generation, lives)) = result_receiver.recv() {
+        each(&game_wrapper.read().unwrap(), lives)?;
+        if generation >= turns {
+            break;
+        }
+        trigger_sender.send(())?;
+    }
+    Ok(())
+}
+
+/// Streams generations for output that is not a terminal.
+fn terminal<E: LifeEngine>(mut game: E, delay: u64, framing: Framing, glyphs: Glyphs) -> Result<(), Box<dyn Error>> {
+    let (width, height) = (game.width(), game.height());
+    let mut viewport = Viewport::new(framing, width, height, &game.lives());
+    let h = viewport.rect().height;
+    let w = viewport.rect().width;
+    let wait = time::Duration::from_millis(delay);
 
     for i in 0.. {
+        let lives = game.lives();
+        viewport.update(width, &lives);
+        let lines = glyphs.lines(w, h, &viewport.crop(width, height, &lives, false));
         if i > 0 {
-            writeln!(std::io::stdout(), "\x1B[{}F", h + 1).unwrap();
+            writeln!(io::stdout(), "\x1B[{}F", lines.len() + 1)?;
         }
-        game.lives().chunks(w).for_each(|row| {
-            let row_string = row.iter().fold(String::with_capacity(w * 2), |a, doa| {
-                if *doa { a + "■ " } else { a + "□ " }
-            });
-            writeln!(std::io::stdout(), "{}", row_string).unwrap();
+        for line in lines {
+            writeln!(io::stdout(), "{}", line)?;
         }
-        );
         game.step();
 
         thread::sleep(wait);
     }
+
+    Ok(())
 }
@@ -0,0 +1,133 @@
+use crate::analysis::bounding_box;
+use crate::engine::Rect;
+
+/// Row-major cells of a `width` x `height` field, for reshaping patterns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<bool>,
+}
+
+impl Pattern {
+    pub fn new(width: usize, height: usize, cells: Vec<bool>) -> Pattern {
+        Pattern { width, height, cells }
+    }
+
+    fn get(&self, x: isize, y: isize) -> bool {
+        0 <= x && x < self.width as isize && 0 <= y && y < self.height as isize
+            && self.cells[y as usize * self.width + x as usize]
+    }
+
+    fn map<F: Fn(usize, usize) -> (isize, isize)>(&self, width: usize, height: usize, from: F) -> Pattern {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let (fx, fy) = from(x, y);
+                self.get(fx, fy)
+            })
+            .collect();
+
+        Pattern { width, height, cells }
+    }
+
+    /// The cells under `rect`, treating anything outside the field as dead.
+    pub fn crop(&self, rect: Rect) -> Pattern {
+        self.map(rect.width, rect.height, |x, y| (rect.x + x as isize, rect.y + y as isize))
+    }
+
+    /// Cropped to the live cells. An empty pattern is left as it is.
+    pub fn trim(&self) -> Pattern {
+        match bounding_box(self.width, &self.cells) {
+            Some(rect) => self.crop(rect),
+            None => self.clone(),
+        }
+    }
+
+    /// Surrounded by `n` dead cells on every side.
+    pub fn pad(&self, n: usize) -> Pattern {
+        let n = n as isize;
+        self.map(self.width + n as usize * 2, self.height + n as usize * 2, |x, y| (x as isize - n, y as isize - n))
+    }
+
+    /// Turned clockwise by `quarters` quarter turns.
+    pub fn rotate(&self, quarters: usize) -> Pattern {
+        let (w, h) = (self.width as isize, self.height as isize);
+
+        match quarters % 4 {
+            1 => self.map(self.height, self.width, |x, y| (y as isize, h - 1 - x as isize)),
+            2 => self.map(self.width, self.height, |x, y| (w - 1 - x as isize, h - 1 - y as isize)),
+            3 => self.map(self.height, self.width, |x, y| (w - 1 - y as isize, x as isize)),
+            _ => self.clone(),
+        }
+    }
+
+    /// Mirrored left to right.
+    pub fn flip_horizontal(&self) -> Pattern {
+        let w = self.width as isize;
+        self.map(self.width, self.height, |x, y| (w - 1 - x as isize, y as isize))
+    }
+
+    /// Mirrored top to bottom.
+    pub fn flip_vertical(&self) -> Pattern {
+        let h = self.height as isize;
+        self.map(self.width, self.height, |x, y| (x as isize, h - 1 - y as isize))
+    }
+}
+
+#[cfg(test)]
+fn l_shape() -> Pattern {
+    Pattern::new(2, 3, vec![
+        true, false,
+        true, false,
+        true, true,
+    ])
+}
+
+#[test]
+fn test_rotate() {
+    assert_eq!(l_shape().rotate(1), Pattern::new(3, 2, vec![
+        true, true, true,
+        true, false, false,
+    ]));
+    assert_eq!(l_shape().rotate(2), Pattern::new(2, 3, vec![
+        true, true,
+        false, true,
+        false, true,
+    ]));
+    assert_eq!(l_shape().rotate(3), Pattern::new(3, 2, vec![
+        false, false, true,
+        true, true, true,
+    ]));
+    assert_eq!(l_shape().rotate(4), l_shape());
+}
+
+#[test]
+fn test_flip() {
+    assert_eq!(l_shape().flip_horizontal(), Pattern::new(2, 3, vec![
+        false, true,
+        false, true,
+        true, true,
+    ]));
+    assert_eq!(l_shape().flip_vertical(), Pattern::new(2, 3, vec![
+        true, true,
+        true, false,
+        true, false,
+    ]));
+}
+
+#[test]
+fn test_crop_trim_pad() {
+    let padded = l_shape().pad(2);
+    assert_eq!((padded.width, padded.height), (6, 7));
+    assert!(padded.get(2, 2) && !padded.get(1, 2));
+    assert_eq!(padded.trim(), l_shape());
+
+    assert_eq!(l_shape().crop(Rect { x: 1, y: 2, width: 2, height: 2 }), Pattern::new(2, 2, vec![
+        true, false,
+        false, false,
+    ]));
+
+    let empty = Pattern::new(2, 2, vec![false; 4]);
+    assert_eq!(empty.trim(), empty);
+}
@@ -0,0 +1,87 @@
+//! Ready-made predicates for `Game::run_until`.
+
+use crate::game::Game;
+
+use std::collections::VecDeque;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+pub fn extinct() -> impl FnMut(&Game) -> bool {
+    |game: &Game| game.population() == 0
+}
+
+pub fn population_at_least(n: usize) -> impl FnMut(&Game) -> bool {
+    move |game: &Game| game.population() >= n
+}
+
+pub fn population_at_most(n: usize) -> impl FnMut(&Game) -> bool {
+    move |game: &Game| game.population() <= n
+}
+
+/// Holds once the world repeats a state seen within the last `max_period` generations.
+/// A still life is detected as period 1; an empty world is not periodic, use `extinct` for that.
+pub fn periodic(max_period: usize) -> impl FnMut(&Game) -> bool {
+    let mut seen: VecDeque<(u64, Vec<bool>)> = VecDeque::with_capacity(max_period);
+
+    move |game: &Game| {
+        if game.population() == 0 {
+            return false;
+        }
+
+        let lives = game.lives();
+        let mut hasher = DefaultHasher::new();
+        lives.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        if seen.iter().any(|(h, seen)| *h == hash && *seen == lives) {
+            return true;
+        }
+
+        seen.push_back((hash, lives));
+        if seen.len() > max_period {
+            seen.pop_front();
+        }
+
+        false
+    }
+}
+
+#[test]
+fn test_extinct() {
+    let mut g = Game::new(3, 3, &[true, false, false]);
+
+    assert_eq!(g.run_until(extinct(), 5), Some(1));
+}
+
+#[test]
+fn test_population() {
+    let mut g = Game::new(3, 3, &[
+        true, true, false,
+        true, false, false,
+    ]);
+
+    assert_eq!(g.run_until(population_at_least(4), 5), Some(1));
+    assert_eq!(g.run_until(population_at_most(3), 5), None);
+}
+
+#[test]
+fn test_periodic() {
+    let blinker = vec![
+        false, false, false, false, false,
+        false, false, false, false, false,
+        false, true, true, true, false,
+    ];
+    let mut g = Game::new(5, 5, &blinker);
+
+    assert_eq!(g.run_until(periodic(1), 10), None);
+
+    let mut g = Game::new(5, 5, &blinker);
+    assert_eq!(g.run_until(periodic(2), 10), Some(2));
+}
+
+#[test]
+fn test_periodic_ignores_extinction() {
+    let mut g = Game::new(3, 3, &[true, false, false]);
+
+    assert_eq!(g.run_until(periodic(1), 5), None);
+}
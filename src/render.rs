@@ -0,0 +1,242 @@
+use crate::standard_error::StandardError;
+use crate::age::Ages;
+
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color {
+    pub const WHITE: Color = Color(0xFF, 0xFF, 0xFF);
+    pub const BLACK: Color = Color(0, 0, 0);
+    pub const GRAY: Color = Color(0x80, 0x80, 0x80);
+}
+
+/// Accepts "rrggbb" with or without a leading "#".
+impl FromStr for Color {
+    type Err = StandardError;
+
+    fn from_str(s: &str) -> Result<Color, StandardError> {
+        let hex = s.trim_start_matches('#');
+        let channel = |i: usize| hex.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+
+        match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Color(r, g, b)),
+            _ => Err(StandardError::new(&format!("invalid color: {:?}", s)))
+        }
+    }
+}
+
+/// Colors for rendering `Ages`: live cells shade from `young` to `old`,
+/// dead cells fade from `trail` to the background.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heat {
+    pub young: Color,
+    pub old: Color,
+    pub trail: Color,
+}
+
+impl Default for Heat {
+    fn default() -> Heat {
+        Heat {
+            young: Color(0xFF, 0xD0, 0),
+            old: Color(0xC0, 0, 0),
+            trail: Color(0x60, 0x80, 0xFF),
+        }
+    }
+}
+
+/// How cells become pixels.
+///
+/// Each cell is a `scale` x `scale` square, optionally separated by 1 pixel grid
+/// lines and surrounded by a `border` pixels wide frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Style {
+    pub scale: usize,
+    pub foreground: Color,
+    pub background: Color,
+    pub grid: Option<Color>,
+    pub border: usize,
+    pub border_color: Color,
+    pub heat: Option<Heat>,
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style {
+            scale: 1,
+            foreground: Color::BLACK,
+            background: Color::WHITE,
+            grid: None,
+            border: 0,
+            border_color: Color::GRAY,
+            heat: None,
+        }
+    }
+}
+
+pub const BACKGROUND: u8 = 0;
+pub const FOREGROUND: u8 = 1;
+pub const GRID: u8 = 2;
+pub const BORDER: u8 = 3;
+/// First of `AGE_LEVELS` indices from young to old, present with `Style::heat`.
+pub const AGE: u8 = 4;
+pub const AGE_LEVELS: u8 = 8;
+/// First of `TRAIL_LEVELS` indices from faint to fresh, present with `Style::heat`.
+pub const TRAIL: u8 = AGE + AGE_LEVELS;
+pub const TRAIL_LEVELS: u8 = 8;
+
+impl Style {
+    /// Pixel size of a `width` x `height` field.
+    pub fn size(&self, width: usize, height: usize) -> (usize, usize) {
+        let gap = if self.grid.is_some() { 1 } else { 0 };
+        let side = |cells: usize| cells * self.scale + cells.saturating_sub(1) * gap + self.border * 2;
+
+        (side(width), side(height))
+    }
+
+    /// RGB triplets indexed by `BACKGROUND`, `FOREGROUND`, `GRID`, `BORDER`
+    /// and, with `heat`, the `AGE` and `TRAIL` levels.
+    pub fn palette(&self) -> Vec<u8> {
+        let grid = self.grid.unwrap_or(self.background);
+        let mut colors = vec![self.background, self.foreground, grid, self.border_color];
+
+        if let Some(heat) = &self.heat {
+            let (ages, trails) = (AGE_LEVELS as usize, TRAIL_LEVELS as usize);
+            colors.extend((0..ages).map(|i| mix(heat.young, heat.old, i, ages - 1)));
+            colors.extend((0..trails).map(|i| mix(self.background, heat.trail, i + 1, trails)));
+        }
+
+        colors.iter()
+            .flat_map(|Color(r, g, b)| vec![*r, *g, *b])
+            .collect()
+    }
+
+    /// Palette indices for `ages`. Ages climb a level each time they double.
+    pub fn heat_cells(&self, ages: &Ages) -> Vec<u8> {
+        let trail = ages.trail().max(1) as usize;
+
+        ages.ages().iter().zip(ages.trails().iter()).map(|(age, fading)| {
+            if *age > 0 {
+                AGE + (31 - age.leading_zeros()).min(AGE_LEVELS as u32 - 1) as u8
+            } else if *fading > 0 {
+                TRAIL + ((*fading as usize - 1) * TRAIL_LEVELS as usize / trail) as u8
+            } else {
+                BACKGROUND
+            }
+        }).collect()
+    }
+
+    /// Palette index per cell, shaded by `ages` when both they and `heat` are present.
+    pub fn cells(&self, lives: &[bool], ages: Option<&Ages>) -> Vec<u8> {
+        match (ages, &self.heat) {
+            (Some(ages), Some(_)) => self.heat_cells(ages),
+            _ => lives.iter().map(|doa| if *doa { FOREGROUND } else { BACKGROUND }).collect()
+        }
+    }
+
+    /// Palette indices for row-major `lives` of a `width` x `height` field.
+    pub fn rasterize(&self, width: usize, height: usize, lives: &[bool]) -> Vec<u8> {
+        self.rasterize_indexed(width, height, &self.cells(lives, None))
+    }
+
+    /// Same as `rasterize` for cells that already carry a palette index.
+    pub fn rasterize_indexed(&self, width: usize, height: usize, cells: &[u8]) -> Vec<u8> {
+        if self.scale == 1 && self.grid.is_none() && self.border == 0 {
+            return cells.to_vec();
+        }
+
+        let (pw, ph) = self.size(width, height);
+        let pitch = self.scale + if self.grid.is_some() { 1 } else { 0 };
+        let mut pixels = vec![BORDER; pw * ph];
+
+        for py in self.border..ph - self.border {
+            let (y, in_y) = ((py - self.border) / pitch, (py - self.border) % pitch);
+            let row = py * pw;
+
+            for px in self.border..pw - self.border {
+                let (x, in_x) = ((px - self.border) / pitch, (px - self.border) % pitch);
+
+                pixels[row + px] = if in_x >= self.scale || in_y >= self.scale {
+                    GRID
+                } else {
+                    cells[y * width + x]
+                };
+            }
+        }
+
+        pixels
+    }
+}
+
+/// `a` moved `num / den` of the way to `b`.
+fn mix(a: Color, b: Color, num: usize, den: usize) -> Color {
+    let channel = |a: u8, b: u8| ((a as usize * (den - num) + b as usize * num) / den.max(1)) as u8;
+    Color(channel(a.0, b.0), channel(a.1, b.1), channel(a.2, b.2))
+}
+
+#[test]
+fn test_color() {
+    assert_eq!(Color::from_str("#ff8000").unwrap(), Color(0xFF, 0x80, 0));
+    assert_eq!(Color::from_str("0a0B0c").unwrap(), Color(0x0A, 0x0B, 0x0C));
+    assert!(Color::from_str("#fff").is_err());
+    assert!(Color::from_str("gg0000").is_err());
+}
+
+#[test]
+fn test_size() {
+    let style = Style { scale: 3, grid: Some(Color::GRAY), border: 2, ..Style::default() };
+
+    assert_eq!(Style::default().size(4, 5), (4, 5));
+    assert_eq!(style.size(4, 1), (3 * 4 + 3 + 4, 3 + 4));
+}
+
+#[test]
+fn test_rasterize_plain() {
+    let lives = vec![true, false, false, true];
+
+    assert_eq!(Style::default().rasterize(2, 2, &lives), vec![1, 0, 0, 1]);
+}
+
+#[test]
+fn test_rasterize_scaled() {
+    let style = Style { scale: 2, grid: Some(Color::GRAY), border: 1, ..Style::default() };
+    let lives = vec![true, false];
+
+    assert_eq!(style.size(2, 1), (7, 4));
+    assert_eq!(style.rasterize(2, 1, &lives), vec![
+        3, 3, 3, 3, 3, 3, 3,
+        3, 1, 1, 2, 0, 0, 3,
+        3, 1, 1, 2, 0, 0, 3,
+        3, 3, 3, 3, 3, 3, 3,
+    ]);
+}
+
+#[test]
+fn test_palette() {
+    let style = Style { foreground: Color(1, 2, 3), ..Style::default() };
+
+    assert_eq!(style.palette(), vec![0xFF, 0xFF, 0xFF, 1, 2, 3, 0xFF, 0xFF, 0xFF, 0x80, 0x80, 0x80]);
+}
+
+#[test]
+fn test_heat_palette() {
+    let style = Style { heat: Some(Heat::default()), ..Style::default() };
+    let palette = style.palette();
+
+    assert_eq!(palette.len(), (TRAIL + TRAIL_LEVELS) as usize * 3);
+    assert_eq!(&palette[AGE as usize * 3..AGE as usize * 3 + 3], &[0xFF, 0xD0, 0]);
+    assert_eq!(&palette[(TRAIL - 1) as usize * 3..TRAIL as usize * 3], &[0xC0, 0, 0]);
+    assert_eq!(&palette[palette.len() - 3..], &[0x60, 0x80, 0xFF]);
+}
+
+#[test]
+fn test_heat_cells() {
+    let mut ages = Ages::new(vec![true, true, false, true].into_iter(), 4);
+    ages.update(vec![true, false, false, true].into_iter());
+    ages.update(vec![true, false, false, true].into_iter());
+    ages.update(vec![true, false, false, false].into_iter());
+
+    let style = Style { heat: Some(Heat::default()), ..Style::default() };
+    assert_eq!(style.heat_cells(&ages), vec![AGE + 2, TRAIL + 2, BACKGROUND, TRAIL + 6]);
+}
@@ -1,38 +1,266 @@
 extern crate regex;
 
 use crate::standard_error::StandardError;
+use crate::rule::Rule;
 
 use std::fs;
 use std::error::Error;
+use std::io::Read;
 use self::regex::Regex;
 use std::str::FromStr;
 
 /// http://www.conwaylife.com/wiki/Run_Length_Encoded
-
 pub struct Rle {}
 
+/// Largest field, margin included, that a pattern may ask for.
+const MAX_CELLS: usize = 1 << 28;
+
+/// The `#N`, `#O` and `#C` lines and declared size of an RLE file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Header {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    pub width: usize,
+    pub height: usize,
+}
+
 impl Rle {
-    pub fn from_file(filename: &str, margin: usize) -> Result<(usize, usize, Vec<bool>), Box<Error>> {
+    pub fn from_file(filename: &str, margin: usize) -> Result<(usize, usize, Vec<bool>), Box<dyn Error>> {
         let raw = fs::read_to_string(filename)?;
         Self::from_string(&raw, margin)
     }
 
-    pub fn from_string(body: &str, margin: usize) -> Result<(usize, usize, Vec<bool>), Box<Error>> {
-        let (setting, data) = split(body.to_string())?;
+    pub fn from_reader<R: Read>(mut r: R, margin: usize) -> Result<(usize, usize, Vec<bool>), Box<dyn Error>> {
+        let mut raw = String::new();
+        r.read_to_string(&mut raw)?;
+        Self::from_string(&raw, margin)
+    }
+
+    pub fn from_string(body: &str, margin: usize) -> Result<(usize, usize, Vec<bool>), Box<dyn Error>> {
+        let (setting, data) = split(body)?;
         let (w, h) = parse_setting(setting)?;
+        let cells = margin.checked_mul(2)
+            .and_then(|pad| (w.checked_add(pad)?).checked_mul(h.checked_add(pad)?))
+            .filter(|cells| *cells <= MAX_CELLS);
+        if w == 0 || h == 0 || cells.is_none() {
+            let message = format!("invalid pattern size: {} x {} with margin {}", w, h, margin);
+            return Err(Box::new(StandardError::new(&message)));
+        }
         let map = parse_map(w, h, margin, data);
 
         Ok((w + margin * 2, h + margin * 2, map))
     }
+
+    /// The rule declared in the header line, if any.
+    pub fn rule(body: &str) -> Result<Option<Rule>, Box<dyn Error>> {
+        let (setting, _) = split(body)?;
+        let rule = Regex::new(r"rule\s*=\s*([^,\s]+)")?;
+
+        match rule.captures(&setting) {
+            Some(cap) => Ok(Some(Rule::from_str(&cap[1])?)),
+            None => Ok(None)
+        }
+    }
+
+    pub fn header(body: &str) -> Result<Header, Box<dyn Error>> {
+        let (setting, _) = split(body)?;
+        let (width, height) = parse_setting(setting)?;
+        let mut header = Header { width, height, ..Header::default() };
+
+        for line in body.lines().filter(|line| line.starts_with('#')) {
+            let text = line.get(2..).unwrap_or("").trim().to_string();
+            match line.get(1..2) {
+                Some("N") => header.name = Some(text),
+                Some("O") => header.author = Some(text),
+                Some("C") | Some("c") => header.comments.push(text),
+                _ => {}
+            }
+        }
+
+        Ok(header)
+    }
+
+    /// `body` with a header line sized to fit its cells added when it has none,
+    /// so bare data such as `2o$obo$o!` can be read.
+    pub fn with_header(body: &str) -> String {
+        if body.lines().any(|line| line.starts_with("x = ")) {
+            return body.to_string();
+        }
+
+        let data: String = body.lines().filter(|line| !line.starts_with('#')).collect();
+        let cells = decode(&data);
+        let width = cells.iter().map(|(x, _)| x + 1).max().unwrap_or(1);
+        let height = cells.iter().map(|(_, y)| y + 1).max().unwrap_or(1);
+
+        format!("x = {}, y = {}\n{}", width, height, body)
+    }
+
+    /// Live cells as written in the data, regardless of the declared size.
+    pub fn live_cells(body: &str) -> Result<Vec<(isize, isize)>, Box<dyn Error>> {
+        let (_, data) = split(body)?;
+        Ok(decode(&data))
+    }
+
+    /// Encodes row-major `lives` of a `width` x `height` field, wrapping lines at 70 characters.
+    pub fn encode(width: usize, height: usize, lives: &[bool], rule: &Rule) -> String {
+        let mut tokens = vec![];
+        let mut pending_rows = 0;
+
+        for row in lives.chunks(width).take(height) {
+            let mut runs: Vec<(usize, char)> = vec![];
+            for doa in row {
+                let tag = if *doa { 'o' } else { 'b' };
+                match runs.last_mut() {
+                    Some((n, t)) if *t == tag => *n += 1,
+                    _ => runs.push((1, tag))
+                }
+            }
+            if let Some((_, 'b')) = runs.last() {
+                runs.pop();
+            }
+
+            if !runs.is_empty() {
+                if pending_rows > 0 {
+                    tokens.push(run(pending_rows, '$'));
+                    pending_rows = 0;
+                }
+                tokens.extend(runs.into_iter().map(|(n, tag)| run(n, tag)));
+            }
+            pending_rows += 1;
+        }
+        tokens.push("!".to_string());
+
+        let mut body = format!("x = {}, y = {}, rule = {}\n", width, height, rule);
+        let mut line_len = 0;
+        for token in tokens {
+            if line_len + token.len() > 70 {
+                body.push('\n');
+                line_len = 0;
+            }
+            line_len += token.len();
+            body += &token;
+        }
+        body.push('\n');
+
+        body
+    }
+}
+
+fn decode(data: &str) -> Vec<(isize, isize)> {
+    let (mut x, mut y) = (0, 0);
+    let mut num = String::new();
+    let mut cells = vec![];
+
+    for c in data.chars() {
+        let n = || usize::from_str(&num).unwrap_or(1) as isize;
+        match c {
+            c if c.is_ascii_digit() => num.push(c),
+            '!' => break,
+            'o' => {
+                cells.extend((x..x + n()).map(|x| (x, y)));
+                x += n();
+            }
+            'b' => x += n(),
+            '$' => {
+                y += n();
+                x = 0;
+            }
+            _ => ()
+        }
+        if !c.is_ascii_digit() {
+            num.clear();
+        }
+    }
+
+    cells
+}
+
+fn run(n: usize, tag: char) -> String {
+    if n == 1 { tag.to_string() } else { format!("{}{}", n, tag) }
+}
+
+#[test]
+fn test_rule() {
+    assert_eq!(Rle::rule("x = 1, y = 2, rule = B36/S23\no!").unwrap(), Some(Rule::from_str("B36/S23").unwrap()));
+    assert_eq!(Rle::rule("x = 1, y = 2\no!").unwrap(), None);
+    assert!(Rle::rule("x = 1, y = 2, rule = life\no!").is_err());
+}
+
+#[test]
+fn test_header() {
+    let body = "#N Glider\n#O Richard K. Guy\n#C The smallest spaceship.\n#C Found in 1969.\nx = 3, y = 3\nbo$2bo$3o!";
+    let header = Rle::header(body).unwrap();
+
+    assert_eq!(header.name, Some("Glider".to_string()));
+    assert_eq!(header.author, Some("Richard K. Guy".to_string()));
+    assert_eq!(header.comments, vec!["The smallest spaceship.", "Found in 1969."]);
+    assert_eq!((header.width, header.height), (3, 3));
+    assert_eq!(Rle::header(&fs::read_to_string("fixtures/valid.rle").unwrap()).unwrap().name, None);
+}
+
+#[test]
+fn test_live_cells() {
+    assert_eq!(Rle::live_cells("x = 3, y = 3\nbo$2bo$3o!").unwrap(), vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+
+    let cells = Rle::live_cells(&fs::read_to_string("fixtures/valid.rle").unwrap()).unwrap();
+    assert_eq!(cells.len(), 22);
+    assert_eq!(cells[0], (13, 0));
+}
+
+#[test]
+fn test_from_reader() {
+    let file = fs::File::open("fixtures/valid.rle").unwrap();
+    assert_eq!(Rle::from_reader(file, 0).unwrap(), Rle::from_file("fixtures/valid.rle", 0).unwrap());
+    assert_eq!(Rle::from_reader("x = 2, y = 1\n2o!".as_bytes(), 0).unwrap(), (2, 1, vec![true, true]));
+}
+
+#[test]
+fn test_with_header() {
+    let body = Rle::with_header("2o$obo$o!");
+    assert_eq!(body, "x = 3, y = 3\n2o$obo$o!");
+    assert_eq!(Rle::from_string(&body, 0).unwrap().2, vec![true, true, false, true, false, true, true, false, false]);
+
+    let full = "x = 5, y = 5\no!";
+    assert_eq!(Rle::with_header(full), full);
+}
+
+#[test]
+fn test_encode() {
+    let lives = vec![
+        false, false, false, false,
+        true, true, false, false,
+        false, false, false, false,
+        false, true, false, true,
+        false, false, false, false,
+    ];
+
+    let body = Rle::encode(4, 5, &lives, &Rule::default());
+    assert_eq!(body, "x = 4, y = 5, rule = B3/S23\n$2o2$bobo!\n");
+    assert_eq!(Rle::from_string(&body, 0).unwrap(), (4, 5, lives));
+}
+
+#[test]
+fn test_encode_wraps() {
+    let lives: Vec<bool> = (0..200).map(|i| i % 3 == 0).collect();
+
+    let body = Rle::encode(200, 1, &lives, &Rule::default());
+    assert!(body.lines().all(|line| line.len() <= 70));
+    assert_eq!(Rle::from_string(&body, 0).unwrap().2, lives);
 }
 
 #[test]
 fn test_parse() {
     assert!(Rle::from_file("fixtures/sample.rl", 0).is_err());
     Rle::from_file("fixtures/valid.rle", 0).unwrap();
+
+    assert!(Rle::from_string("x = 0, y = 0\n!", 0).is_err());
+    assert!(Rle::from_string("x = 0, y = 3\n!", 2).is_err());
+    assert!(Rle::from_string("x = 100000, y = 100000\no!", 0).is_err());
+    assert!(Rle::from_string("x = 1, y = 1\no!", usize::MAX).is_err());
 }
 
-fn split(raw: String) -> Result<(String, String), Box<Error>> {
+fn split(raw: &str) -> Result<(String, String), Box<dyn Error>> {
     let mut setting: String = "".to_string();
     let mut data: String = "".to_string();
 
@@ -42,7 +270,7 @@ fn split(raw: String) -> Result<(String, String), Box<Error>> {
         } else if line.starts_with("x = ") {
             setting = line.to_string();
         } else {
-            data += &line.to_string();
+            data += line;
         }
     }
 
@@ -56,18 +284,18 @@ fn split(raw: String) -> Result<(String, String), Box<Error>> {
 #[test]
 fn test_split() {
     let raw = fs::read_to_string("fixtures/valid.rle").unwrap();
-    let (setting, data) = split(raw).unwrap();
+    let (setting, data) = split(&raw).unwrap();
     assert_eq!(setting, "x = 1, y = 2, rule = B3/S23".to_string());
     assert_eq!(data, "13b22o!".to_string());
 
     let raw = fs::read_to_string("fixtures/no_data.rle").unwrap();
-    assert!(split(raw).is_err());
+    assert!(split(&raw).is_err());
 
     let raw = fs::read_to_string("fixtures/no_setting.rle").unwrap();
-    assert!(split(raw).is_err());
+    assert!(split(&raw).is_err());
 }
 
-fn parse_setting(line: String) -> Result<(usize, usize), Box<Error>> {
+fn parse_setting(line: String) -> Result<(usize, usize), Box<dyn Error>> {
     let setting = Regex::new(r"x = ([0-9]+), y = ([0-9]+)")?;
 
     let cap = match setting.captures(line.as_str()) {
@@ -91,8 +319,7 @@ fn parse_map(raw_w: usize, raw_h: usize, margin: usize, life_map: String) -> Vec
     let w = raw_w + margin * 2;
     let h = raw_h + margin * 2;
     let mut num = "".to_string();
-    let total = w * h;
-    let mut lives = Vec::with_capacity(total);
+    let mut lives = vec![false; w * h];
 
     // # format
     //
@@ -113,41 +340,36 @@ fn parse_map(raw_w: usize, raw_h: usize, margin: usize, life_map: String) -> Vec
     //
     // Dead cells between last alive cell in a line and "$" can be omitted.
     //
+    // Cells beyond the declared size are dropped.
+    //
 
-    lives.extend(vec![false; w * margin + margin]);
-
-    let mut rows = 0;
+    let (mut x, mut y) = (0, 0);
     for c in life_map.chars() {
         match c {
-            c if c.is_digit(10) => num.push(c),
+            c if c.is_ascii_digit() => num.push(c),
             '!' => break,
             'b' | 'o' => {
                 let n = usize::from_str(&num).unwrap_or(1);
 
-                lives.extend(vec![is_alive(c); n]);
+                if is_alive(c) && y < raw_h {
+                    for cx in x..(x + n).min(raw_w) {
+                        lives[(y + margin) * w + cx + margin] = true;
+                    }
+                }
 
                 num.clear();
-                rows += n;
+                x += n;
             },
             '$' => {
                 let n = usize::from_str(&num).unwrap_or(1);
-                if rows < w {
-                    lives.extend(vec![false; w - rows]);
-                }
-                if n > 1 {
-                    lives.extend(vec![false; w * (n - 1)]);
-                }
                 num.clear();
-                rows = 0;
+                x = 0;
+                y += n;
             },
             _ => ()
         }
     }
 
-    if lives.len() < total {
-        lives.extend(vec![false; total - lives.len()]);
-    }
-
     lives
 }
 
@@ -174,4 +396,10 @@ fn test_parse_map() {
         false, false, false, false, false, false, false, false,
         false, false, false, false, false, false, false, false,
     ]);
+
+    assert_eq!(parse_map(3, 2, 0, "5bo$o2bo!".to_string()), vec![
+        false, false, false,
+        true, false, false,
+    ]);
+    assert_eq!(parse_map(2, 1, 0, "bo$o!".to_string()), vec![false, true]);
 }
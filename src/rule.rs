@@ -0,0 +1,104 @@
+use crate::standard_error::StandardError;
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Outer-totalistic rule such as "B3/S23".
+///
+/// Bit `n` of `birth` / `survival` is set when a cell with `n` live neighbours
+/// is born / survives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    birth: u16,
+    survival: u16,
+}
+
+impl Rule {
+    pub fn from_masks(birth: u16, survival: u16) -> Rule {
+        Rule { birth: birth & 0x1FF, survival: survival & 0x1FF }
+    }
+
+    pub fn masks(&self) -> (u16, u16) {
+        (self.birth, self.survival)
+    }
+
+    pub fn next(&self, now: bool, lives: usize) -> bool {
+        let mask = if now { self.survival } else { self.birth };
+        mask & 1 << lives != 0
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Rule {
+        Rule { birth: 1 << 3, survival: 1 << 2 | 1 << 3 }
+    }
+}
+
+/// Accepts "B3/S23" and the older "23/3" (survival/birth) notation.
+impl FromStr for Rule {
+    type Err = StandardError;
+
+    fn from_str(s: &str) -> Result<Rule, StandardError> {
+        let invalid = || StandardError::new(&format!("invalid rule: {:?}", s));
+
+        let parts: Vec<&str> = s.trim().split('/').collect();
+        if parts.len() != 2 {
+            return Err(invalid());
+        }
+
+        let (b, s) = match (parts[0].chars().next(), parts[1].chars().next()) {
+            (Some('B'), Some('S')) | (Some('b'), Some('s')) => (&parts[0][1..], &parts[1][1..]),
+            (Some('S'), Some('B')) | (Some('s'), Some('b')) => (&parts[1][1..], &parts[0][1..]),
+            _ => (parts[1], parts[0]),
+        };
+
+        match (digits(b), digits(s)) {
+            (Some(birth), Some(survival)) => Ok(Rule { birth, survival }),
+            _ => Err(invalid())
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let list = |mask: u16| (0..9).filter(|n| mask & 1 << n != 0).map(|n| n.to_string()).collect::<String>();
+        write!(f, "B{}/S{}", list(self.birth), list(self.survival))
+    }
+}
+
+fn digits(s: &str) -> Option<u16> {
+    s.chars().try_fold(0, |mask, c| match c.to_digit(10) {
+        Some(n) if n <= 8 => Some(mask | 1 << n),
+        _ => None
+    })
+}
+
+#[test]
+fn test_from_str() {
+    assert_eq!(Rule::from_str("B3/S23").unwrap(), Rule::default());
+    assert_eq!(Rule::from_str("b3/s23").unwrap(), Rule::default());
+    assert_eq!(Rule::from_str("S23/B3").unwrap(), Rule::default());
+    assert_eq!(Rule::from_str("23/3").unwrap(), Rule::default());
+    assert_eq!(Rule::from_str("B36/S23").unwrap().masks(), (1 << 3 | 1 << 6, 1 << 2 | 1 << 3));
+    assert_eq!(Rule::from_str("B2/S").unwrap().masks(), (1 << 2, 0));
+
+    assert!(Rule::from_str("B3").is_err());
+    assert!(Rule::from_str("B39/S23").is_err());
+    assert!(Rule::from_str("life").is_err());
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(Rule::default().to_string(), "B3/S23");
+    assert_eq!(Rule::from_str("36/23").unwrap().to_string(), "B23/S36");
+}
+
+#[test]
+fn test_next() {
+    let life = Rule::default();
+
+    assert!(life.next(false, 3));
+    assert!(!life.next(false, 2));
+    assert!(life.next(true, 2));
+    assert!(!life.next(true, 4));
+}
@@ -0,0 +1,198 @@
+use crate::game::Game;
+use crate::engine::LifeEngine;
+use crate::standard_error::StandardError;
+use crate::rule::Rule;
+
+use std::fs::{self, File};
+use std::error::Error;
+use std::io::{Read, Write, BufReader, BufWriter};
+
+static MAGIC: &[u8; 4] = b"LGSN";
+const VERSION: u8 = 2;
+
+/// Checkpoint of a whole `Game`.
+///
+/// # format (version 2)
+///
+/// "LGSN"                 magic
+/// u8                     version
+/// u64 le                 width
+/// u64 le                 height
+/// u64 le                 generation
+/// u16 le, u16 le         birth and survival masks of the rule (absent in version 1, which is B3/S23)
+/// [u8; (w * h + 7) / 8]  live cells, row-major, least significant bit first
+pub struct Snapshot {}
+
+impl Snapshot {
+    /// Writes through a temporary file so an interrupted save leaves the previous checkpoint intact.
+    pub fn to_file<E: LifeEngine + ?Sized>(game: &E, filename: &str) -> Result<(), Box<dyn Error>> {
+        let tmp = format!("{}.tmp", filename);
+        {
+            let mut w = BufWriter::new(File::create(&tmp)?);
+            Self::write(game, &mut w)?;
+            w.flush()?;
+        }
+        fs::rename(&tmp, filename)?;
+
+        Ok(())
+    }
+
+    pub fn from_file(filename: &str) -> Result<Game, Box<dyn Error>> {
+        Self::read(&mut BufReader::new(File::open(filename)?))
+    }
+
+    /// Whether `filename` starts like a snapshot, as opposed to a pattern file.
+    pub fn sniff(filename: &str) -> bool {
+        let mut head = [0; 4];
+        match File::open(filename) {
+            Ok(mut f) => f.read_exact(&mut head).is_ok() && Self::matches(&head),
+            _ => false
+        }
+    }
+
+    /// Same as `sniff` for contents already read.
+    pub fn matches(bytes: &[u8]) -> bool {
+        bytes.starts_with(MAGIC)
+    }
+
+    pub fn write<E: LifeEngine + ?Sized, W: Write>(game: &E, w: &mut W) -> Result<(), Box<dyn Error>> {
+        w.write_all(MAGIC)?;
+        w.write_all(&[VERSION])?;
+        w.write_all(&(game.width() as u64).to_le_bytes())?;
+        w.write_all(&(game.height() as u64).to_le_bytes())?;
+        w.write_all(&(game.generation() as u64).to_le_bytes())?;
+        let (birth, survival) = game.rule().masks();
+        w.write_all(&birth.to_le_bytes())?;
+        w.write_all(&survival.to_le_bytes())?;
+        w.write_all(&pack(&game.lives()))?;
+
+        Ok(())
+    }
+
+    pub fn read<R: Read>(r: &mut R) -> Result<Game, Box<dyn Error>> {
+        let mut head = [0; 5];
+        r.read_exact(&mut head)?;
+
+        if &head[0..4] != MAGIC {
+            return Err(Box::new(StandardError::new("not a snapshot")))
+        }
+        if head[4] != 1 && head[4] != VERSION {
+            return Err(Box::new(StandardError::new(&format!("unsupported snapshot version: {}", head[4]))))
+        }
+
+        let width = read_u64(r)? as usize;
+        let height = read_u64(r)? as usize;
+        let generation = read_u64(r)? as usize;
+        let rule = if head[4] == 1 {
+            Rule::default()
+        } else {
+            Rule::from_masks(read_u16(r)?, read_u16(r)?)
+        };
+
+        let cells = match width.checked_mul(height) {
+            Some(0) | None => return Err(Box::new(StandardError::new(&format!("invalid snapshot size: {} x {}", width, height)))),
+            Some(cells) => cells,
+        };
+
+        // Read no further than the stream goes rather than allocating whatever the header claims.
+        let mut packed = vec![];
+        r.take(cells.div_ceil(8) as u64).read_to_end(&mut packed)?;
+        if packed.len() < cells.div_ceil(8) {
+            return Err(Box::new(StandardError::new("snapshot is truncated")))
+        }
+
+        let mut game = Game::new(width, height, &unpack(&packed, cells));
+        game.set_rule(rule);
+        game.resume_at(generation);
+
+        Ok(game)
+    }
+}
+
+fn read_u64<R: Read>(r: &mut R) -> Result<u64, Box<dyn Error>> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_u16<R: Read>(r: &mut R) -> Result<u16, Box<dyn Error>> {
+    let mut buf = [0; 2];
+    r.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn pack(lives: &[bool]) -> Vec<u8> {
+    lives.chunks(8).map(|byte| {
+        byte.iter().enumerate().fold(0u8, |a, (i, doa)| if *doa { a | 1 << i } else { a })
+    }).collect()
+}
+
+fn unpack(packed: &[u8], len: usize) -> Vec<bool> {
+    (0..len).map(|i| packed[i / 8] & 1 << (i % 8) != 0).collect()
+}
+
+#[test]
+fn test_pack() {
+    let lives = vec![true, false, false, true, false, false, false, false, true, true];
+    let packed = pack(&lives);
+
+    assert_eq!(packed, vec![0b0000_1001, 0b0000_0011]);
+    assert_eq!(unpack(&packed, lives.len()), lives);
+}
+
+#[test]
+fn test_round_trip() {
+    let blinker = vec![
+        false, false, false,
+        true, true, true,
+        false, false, false,
+    ];
+    let mut g = Game::new(3, 3, &blinker);
+    g.set_rule(std::str::FromStr::from_str("B3/S234").unwrap());
+    g.step_n(3);
+
+    let mut buf = vec![];
+    Snapshot::write(&g, &mut buf).unwrap();
+    let mut restored = Snapshot::read(&mut buf.as_slice()).unwrap();
+
+    assert_eq!((restored.width, restored.height), (3, 3));
+    assert_eq!(restored.generation(), 3);
+    assert_eq!(restored.rule(), g.rule());
+    assert_eq!(restored.lives(), g.lives());
+
+    g.step();
+    restored.step();
+    assert_eq!(restored.lives(), g.lives());
+}
+
+#[test]
+fn test_read_version_1() {
+    let mut buf = b"LGSN\x01".to_vec();
+    buf.extend(&2u64.to_le_bytes());
+    buf.extend(&2u64.to_le_bytes());
+    buf.extend(&7u64.to_le_bytes());
+    buf.push(0b0000_1001);
+
+    let restored = Snapshot::read(&mut buf.as_slice()).unwrap();
+    assert_eq!(restored.generation(), 7);
+    assert_eq!(restored.rule(), Rule::default());
+    assert_eq!(restored.lives(), vec![true, false, false, true]);
+}
+
+#[test]
+fn test_read_invalid() {
+    assert!(Snapshot::read(&mut &b"x = 1, y = 2"[..]).is_err());
+    assert!(Snapshot::read(&mut &b"LGSN\x09"[..]).is_err());
+
+    let header = |width: u64, height: u64| {
+        let mut buf = b"LGSN\x01".to_vec();
+        buf.extend(&width.to_le_bytes());
+        buf.extend(&height.to_le_bytes());
+        buf.extend(&0u64.to_le_bytes());
+        buf
+    };
+    assert!(Snapshot::read(&mut header(0, 3).as_slice()).is_err());
+    assert!(Snapshot::read(&mut header(u64::MAX, 2).as_slice()).is_err());
+    assert!(Snapshot::read(&mut header(1 << 20, 1 << 20).as_slice()).is_err());
+    assert!(!Snapshot::sniff("fixtures/valid.rle"));
+}
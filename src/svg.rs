@@ -0,0 +1,145 @@
+use crate::engine::Rect;
+use crate::render::{Color, Style, BACKGROUND};
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Room left of and above the field for coordinate labels.
+const GUTTER: usize = 24;
+const FONT_SIZE: usize = 10;
+
+/// Writes palette-indexed `cells` covering `rect` as an SVG drawn with `style`.
+///
+/// Horizontal runs of the same color become a single rectangle. With `labels`,
+/// field coordinates of `rect` are printed along the top and left edges.
+pub fn write_svg<W: Write>(mut w: W, rect: &Rect, cells: &[u8], style: &Style, labels: bool) -> Result<(), Box<dyn Error>> {
+    let (width, height) = (rect.width, rect.height);
+    let (pixel_width, pixel_height) = style.size(width, height);
+    let gutter = if labels { GUTTER } else { 0 };
+    let pitch = style.scale + if style.grid.is_some() { 1 } else { 0 };
+    let palette = style.palette();
+    let fill = |index: u8| {
+        let i = index as usize * 3;
+        hex(Color(palette[i], palette[i + 1], palette[i + 2]))
+    };
+
+    writeln!(w, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" shape-rendering="crispEdges">"#,
+             pixel_width + gutter, pixel_height + gutter)?;
+    writeln!(w, r#"<g transform="translate({0},{0})">"#, gutter)?;
+    if style.border > 0 {
+        writeln!(w, r#"<rect width="{}" height="{}" fill="{}"/>"#, pixel_width, pixel_height, hex(style.border_color))?;
+    }
+    writeln!(w, r#"<rect x="{0}" y="{0}" width="{1}" height="{2}" fill="{3}"/>"#,
+             style.border, pixel_width - style.border * 2, pixel_height - style.border * 2, fill(BACKGROUND))?;
+
+    for y in 0..height {
+        let row = &cells[y * width..(y + 1) * width];
+        let mut x = 0;
+        while x < width {
+            let run = row[x..].iter().take_while(|index| **index == row[x]).count();
+            if row[x] != BACKGROUND {
+                writeln!(w, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                         style.border + x * pitch, style.border + y * pitch, run * pitch - (pitch - style.scale), style.scale, fill(row[x]))?;
+            }
+            x += run;
+        }
+    }
+
+    if let Some(grid) = style.grid {
+        let mut path = String::new();
+        for x in 1..width {
+            path += &format!("M{}.5 {}v{}", style.border + x * pitch - 1, style.border, pixel_height - style.border * 2);
+        }
+        for y in 1..height {
+            path += &format!("M{} {}.5h{}", style.border, style.border + y * pitch - 1, pixel_width - style.border * 2);
+        }
+        if !path.is_empty() {
+            writeln!(w, r#"<path d="{}" stroke="{}" stroke-width="1"/>"#, path, hex(grid))?;
+        }
+    }
+
+    writeln!(w, "</g>")?;
+
+    if labels {
+        let step = label_step(pitch);
+        let center = |i: usize| gutter + style.border + i * pitch + style.scale / 2;
+
+        writeln!(w, r#"<g font-family="monospace" font-size="{}" fill="{}">"#, FONT_SIZE, hex(style.foreground))?;
+        for (i, x) in (rect.x..rect.x + width as isize).enumerate().filter(|(_, x)| x % step == 0) {
+            writeln!(w, r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#, center(i), gutter - 4, x)?;
+        }
+        for (i, y) in (rect.y..rect.y + height as isize).enumerate().filter(|(_, y)| y % step == 0) {
+            writeln!(w, r#"<text x="{}" y="{}" text-anchor="end" dominant-baseline="middle">{}</text>"#, gutter - 4, center(i), y)?;
+        }
+        writeln!(w, "</g>")?;
+    }
+
+    writeln!(w, "</svg>")?;
+    Ok(())
+}
+
+pub fn save_svg(filename: &str, rect: &Rect, cells: &[u8], style: &Style, labels: bool) -> Result<(), Box<dyn Error>> {
+    let mut w = BufWriter::new(File::create(filename)?);
+    write_svg(&mut w, rect, cells, style, labels)?;
+    w.flush()?;
+
+    Ok(())
+}
+
+fn hex(Color(r, g, b): Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Smallest of 1, 2, 5, 10, 20, 50... cells that keeps labels `GUTTER` pixels apart.
+fn label_step(pitch: usize) -> isize {
+    let mut magnitude = 1;
+    loop {
+        for step in &[magnitude, magnitude * 2, magnitude * 5] {
+            if step * pitch.max(1) >= GUTTER {
+                return *step as isize;
+            }
+        }
+        magnitude *= 10;
+    }
+}
+
+#[cfg(test)]
+fn svg_of(rect: &Rect, cells: &[u8], style: &Style, labels: bool) -> String {
+    let mut buf = vec![];
+    write_svg(&mut buf, rect, cells, style, labels).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_write_svg_runs() {
+    let rect = Rect { x: 0, y: 0, width: 4, height: 2 };
+    let svg = svg_of(&rect, &[1, 1, 0, 1, 0, 0, 0, 0], &Style { scale: 10, ..Style::default() }, false);
+
+    assert!(svg.starts_with(r##"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20""##));
+    assert!(svg.contains(r##"<rect x="0" y="0" width="20" height="10" fill="#000000"/>"##));
+    assert!(svg.contains(r##"<rect x="30" y="0" width="10" height="10" fill="#000000"/>"##));
+    assert_eq!(svg.matches("<rect").count(), 3);
+    assert!(!svg.contains("<path") && !svg.contains("<text"));
+}
+
+#[test]
+fn test_write_svg_grid_and_labels() {
+    let style = Style { scale: 4, grid: Some(Color::GRAY), border: 2, ..Style::default() };
+    let rect = Rect { x: -5, y: 10, width: 3, height: 1 };
+    let svg = svg_of(&rect, &[1, 1, 0], &style, true);
+
+    assert!(svg.contains(r##"width="42" height="32""##));
+    assert!(svg.contains(r##"<rect width="18" height="8" fill="#808080"/>"##));
+    assert!(svg.contains(r##"<rect x="2" y="2" width="9" height="4" fill="#000000"/>"##));
+    assert!(svg.contains(r##"<path d="M6.5 2v4M11.5 2v4" stroke="#808080""##));
+    assert!(svg.contains(">-5</text>") && !svg.contains(">-4</text>"));
+    assert!(svg.contains(">10</text>"));
+}
+
+#[test]
+fn test_label_step() {
+    assert_eq!(label_step(1), 50);
+    assert_eq!(label_step(5), 5);
+    assert_eq!(label_step(30), 1);
+}
@@ -0,0 +1,384 @@
+use crate::engine::LifeEngine;
+use crate::game::Game;
+use crate::glyph::Glyphs;
+use crate::viewport::{Framing, Viewport};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::error::Error;
+use std::io::{self, BufWriter, Write};
+use std::time::{Duration, Instant};
+
+/// Generations kept for stepping back with `b`.
+const HISTORY: usize = 1000;
+const MAX_DELAY: u64 = 10_000;
+/// Unchanged characters worth reprinting to save moving the cursor past them.
+const MAX_GAP: usize = 8;
+
+/// Interactive terminal viewer.
+///
+/// Space pauses, `n` single-steps, `b` steps back, arrows pan, `+`/`-` change
+/// the speed, `z` switches to the next `Glyphs`, `r` reloads the pattern through
+/// `reload` and `q` quits. The viewport always fills the terminal, which is
+/// redrawn at most `fps` times a second however fast the game runs.
+pub struct Tui<R: FnMut() -> Result<Game, Box<dyn Error>>> {
+    game: Game,
+    reload: R,
+    framing: Framing,
+    viewport: Viewport,
+    glyphs: Glyphs,
+    delay: u64,
+    fps: u64,
+    paused: bool,
+    message: Option<String>,
+    painter: Painter,
+}
+
+impl<R: FnMut() -> Result<Game, Box<dyn Error>>> Tui<R> {
+    pub fn new(mut game: Game, framing: Framing, glyphs: Glyphs, delay: u64, reload: R) -> Tui<R> {
+        game.set_history_depth(HISTORY);
+        let viewport = Viewport::new(framing, game.width(), game.height(), &game.lives());
+
+        Tui {
+            game,
+            reload,
+            framing,
+            viewport,
+            glyphs,
+            delay,
+            fps: 30,
+            paused: false,
+            message: None,
+            painter: Painter::default(),
+        }
+    }
+
+    pub fn set_fps(&mut self, fps: u64) {
+        self.fps = fps.max(1);
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    pub fn glyphs(&self) -> Glyphs {
+        self.glyphs
+    }
+
+    pub fn delay(&self) -> u64 {
+        self.delay
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Takes over the terminal until `q` is pressed, restoring it afterwards even on errors.
+    pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
+        let _screen = Screen::enter()?;
+        let mut out = BufWriter::new(io::stdout());
+        let frame = Duration::from_millis(1000 / self.fps);
+        let mut next_step = Instant::now() + Duration::from_millis(self.delay);
+        let mut next_frame = Instant::now();
+        let mut dirty = true;
+
+        loop {
+            if dirty && Instant::now() >= next_frame {
+                let (cols, rows) = terminal::size()?;
+                self.draw(&mut out, cols as usize, rows as usize)?;
+                next_frame = Instant::now() + frame;
+                dirty = false;
+            }
+
+            let mut deadline = if self.paused { Instant::now() + Duration::from_secs(3600) } else { next_step };
+            if dirty {
+                deadline = deadline.min(next_frame);
+            }
+
+            if event::poll(deadline.saturating_duration_since(Instant::now()))? {
+                match event::read()? {
+                    Event::Key(key) if key.kind != KeyEventKind::Release => {
+                        let interrupt = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                        if interrupt || !self.handle(key.code) {
+                            return Ok(());
+                        }
+                        dirty = true;
+                    }
+                    Event::Resize(..) => dirty = true,
+                    _ => {}
+                }
+            }
+
+            if !self.paused && Instant::now() >= next_step {
+                self.step();
+                next_step = Instant::now() + Duration::from_millis(self.delay);
+                dirty = true;
+            }
+        }
+    }
+
+    /// Applies a key press, returning false when the viewer should quit.
+    pub fn handle(&mut self, key: KeyCode) -> bool {
+        let rect = self.viewport.rect();
+        let (dx, dy) = ((rect.width / 8).max(1) as isize, (rect.height / 8).max(1) as isize);
+        self.message = None;
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char(' ') => self.paused = !self.paused,
+            KeyCode::Char('n') => {
+                self.paused = true;
+                self.step();
+            }
+            KeyCode::Char('b') => {
+                self.paused = true;
+                if self.game.step_back() {
+                    self.viewport.update(self.game.width(), &self.game.lives());
+                } else {
+                    self.message = Some("no earlier generation kept".to_string());
+                }
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => self.delay = (self.delay / 2).max(1).min(self.delay),
+            KeyCode::Char('-') => self.delay = (self.delay * 2).clamp(1, MAX_DELAY),
+            KeyCode::Char('z') => self.glyphs = self.glyphs.next(),
+            KeyCode::Left => self.viewport.pan(-dx, 0),
+            KeyCode::Right => self.viewport.pan(dx, 0),
+            KeyCode::Up => self.viewport.pan(0, -dy),
+            KeyCode::Down => self.viewport.pan(0, dy),
+            KeyCode::Char('r') => match (self.reload)() {
+                Ok(mut game) => {
+                    game.set_history_depth(HISTORY);
+                    self.viewport = Viewport::new(self.framing, game.width(), game.height(), &game.lives());
+                    self.game = game;
+                }
+                Err(e) => self.message = Some(format!("reload failed: {}", e)),
+            },
+            _ => {}
+        }
+
+        true
+    }
+
+    pub fn status(&self) -> String {
+        let mut status = format!("gen {} | pop {} | {} | {} ms",
+                                 self.game.generation(), self.game.population(), self.game.rule(), self.delay);
+        if self.paused {
+            status += " | paused";
+        }
+        if let Some(message) = &self.message {
+            status += " | ";
+            status += message;
+        }
+        status
+    }
+
+    fn step(&mut self) {
+        self.game.step();
+        self.viewport.update(self.game.width(), &self.game.lives());
+    }
+
+    /// Draws the viewport fitted to `cols` x `rows`, with the status line at the bottom.
+    fn draw<W: Write>(&mut self, w: &mut W, cols: usize, rows: usize) -> io::Result<()> {
+        let (fit_width, fit_height) = self.glyphs.fit(cols, rows.saturating_sub(1));
+        self.viewport.resize(fit_width, fit_height);
+
+        let (width, height) = (self.game.width(), self.game.height());
+        let cells = self.viewport.crop(width, height, &self.game.lives(), false);
+        let mut lines = self.glyphs.lines(fit_width, fit_height, &cells);
+        lines.truncate(rows.saturating_sub(1));
+        lines.push(self.status().chars().take(cols).collect());
+
+        self.painter.paint(w, (cols, rows), &lines)
+    }
+}
+
+/// Screen contents as last painted, so each frame only rewrites what changed.
+#[derive(Debug, Default)]
+struct Painter {
+    size: (usize, usize),
+    lines: Vec<Vec<char>>,
+}
+
+impl Painter {
+    /// Brings a `cols` x `rows` screen showing the previous `lines` up to date with `lines`.
+    fn paint<W: Write>(&mut self, w: &mut W, size: (usize, usize), lines: &[String]) -> io::Result<()> {
+        if size != self.size {
+            queue!(w, Clear(ClearType::All))?;
+            self.size = size;
+            self.lines.clear();
+        }
+
+        let lines: Vec<Vec<char>> = lines.iter().map(|line| line.chars().collect()).collect();
+        let last = lines.len().saturating_sub(1);
+
+        for (row, line) in lines.iter().enumerate() {
+            // The last line is the status line, kept at the bottom of the screen.
+            let y = if row == last { size.1.saturating_sub(1) } else { row };
+            let old = self.lines.get(row).map(|old| old.as_slice()).unwrap_or(&[]);
+
+            for (start, end) in spans(old, line) {
+                let text: String = (start..end).map(|x| line.get(x).cloned().unwrap_or(' ')).collect();
+                queue!(w, MoveTo(start as u16, y as u16), Print(text))?;
+            }
+        }
+
+        self.lines = lines;
+        w.flush()
+    }
+}
+
+/// `start..end` column ranges where `new` differs from `old`.
+fn spans(old: &[char], new: &[char]) -> Vec<(usize, usize)> {
+    let mut spans: Vec<(usize, usize)> = vec![];
+
+    for x in (0..old.len().max(new.len())).filter(|x| old.get(*x) != new.get(*x)) {
+        match spans.last_mut() {
+            Some((_, end)) if x - *end <= MAX_GAP => *end = x + 1,
+            _ => spans.push((x, x + 1)),
+        }
+    }
+
+    spans
+}
+
+/// Raw mode on the alternate screen for as long as it lives.
+struct Screen;
+
+impl Screen {
+    fn enter() -> io::Result<Screen> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[cfg(test)]
+fn blinker_tui() -> Tui<impl FnMut() -> Result<Game, Box<dyn Error>>> {
+    let blinker = || {
+        let mut lives = vec![false; 25];
+        lives[11..14].iter_mut().for_each(|doa| *doa = true);
+        Ok(Game::new(5, 5, &lives))
+    };
+
+    Tui::new(blinker().unwrap(), Framing::Full, Glyphs::Squares, 100, blinker)
+}
+
+#[test]
+fn test_handle_pause_and_step() {
+    let mut tui = blinker_tui();
+
+    assert!(tui.handle(KeyCode::Char(' ')));
+    assert!(tui.paused());
+
+    tui.handle(KeyCode::Char('n'));
+    tui.handle(KeyCode::Char('n'));
+    assert_eq!(tui.game().generation(), 2);
+    assert!(tui.paused());
+
+    tui.handle(KeyCode::Char('b'));
+    assert_eq!(tui.game().generation(), 1);
+    assert!(tui.game().is_live(2, 1));
+
+    tui.handle(KeyCode::Char('r'));
+    assert_eq!(tui.game().generation(), 0);
+    assert!(!tui.handle(KeyCode::Char('q')));
+}
+
+#[test]
+fn test_handle_speed_and_pan() {
+    use crate::engine::Rect;
+
+    let mut tui = blinker_tui();
+
+    tui.handle(KeyCode::Char('+'));
+    assert_eq!(tui.delay(), 50);
+    tui.handle(KeyCode::Char('-'));
+    tui.handle(KeyCode::Char('-'));
+    assert_eq!(tui.delay(), 200);
+    for _ in 0..10 {
+        tui.handle(KeyCode::Char('+'));
+    }
+    assert_eq!(tui.delay(), 1);
+
+    let mut fastest = Tui::new(Game::new(1, 1, &[false]), Framing::Full, Glyphs::Squares, 0, || Ok(Game::new(1, 1, &[false])));
+    fastest.handle(KeyCode::Char('+'));
+    assert_eq!(fastest.delay(), 0);
+
+    tui.handle(KeyCode::Right);
+    tui.handle(KeyCode::Up);
+    assert_eq!(tui.viewport().rect(), Rect { x: 1, y: -1, width: 5, height: 5 });
+}
+
+#[test]
+fn test_draw() {
+    use crate::engine::Rect;
+
+    let mut tui = blinker_tui();
+    tui.handle(KeyCode::Char(' '));
+
+    let mut buf = vec![];
+    tui.draw(&mut buf, 8, 3).unwrap();
+    let screen = String::from_utf8(buf).unwrap();
+
+    assert_eq!(tui.viewport().rect(), Rect { x: 0, y: 1, width: 4, height: 2 });
+    assert!(screen.contains("□ □ □ □ "));
+    assert!(!screen.contains("□ □ □ □ □"));
+    assert!(screen.contains("□ ■ ■ ■ "));
+    assert!(screen.contains("gen 0 |"));
+    assert!(!screen.contains("pop 3"));
+
+    tui.handle(KeyCode::Char('z'));
+    tui.handle(KeyCode::Char('z'));
+    assert_eq!(tui.glyphs(), Glyphs::Braille);
+
+    let mut buf = vec![];
+    tui.draw(&mut buf, 3, 2).unwrap();
+    assert_eq!(tui.viewport().rect(), Rect { x: -1, y: 0, width: 6, height: 4 });
+    assert!(String::from_utf8(buf).unwrap().contains("\u{2800}\u{2824}\u{2804}"));
+
+    assert_eq!(tui.status(), "gen 0 | pop 3 | B3/S23 | 100 ms | paused");
+}
+
+#[test]
+fn test_draw_changes_only() {
+    let mut tui = blinker_tui();
+    tui.handle(KeyCode::Char(' '));
+
+    let mut buf = vec![];
+    tui.draw(&mut buf, 10, 6).unwrap();
+    let full = buf.len();
+
+    let mut buf = vec![];
+    tui.draw(&mut buf, 10, 6).unwrap();
+    assert!(buf.is_empty());
+
+    tui.handle(KeyCode::Char('n'));
+    let mut buf = vec![];
+    tui.draw(&mut buf, 10, 6).unwrap();
+    assert!(buf.len() < full / 2);
+    assert!(String::from_utf8(buf).unwrap().contains("\x1B[6;5H1"));
+}
+
+#[test]
+fn test_spans() {
+    let chars = |s: &str| s.chars().collect::<Vec<char>>();
+
+    assert_eq!(spans(&chars("abc"), &chars("abc")), vec![]);
+    assert_eq!(spans(&chars("abcdefghijklmnop"), &chars("Abcdefghijklmnop")), vec![(0, 1)]);
+    assert_eq!(spans(&chars("abcdefghijklmnop"), &chars("AbCdefghijklmnoP")), vec![(0, 3), (15, 16)]);
+    assert_eq!(spans(&chars("abcd"), &chars("ab")), vec![(2, 4)]);
+}
@@ -0,0 +1,272 @@
+use crate::animation::{Animation, Format};
+use crate::engine::Rect;
+use crate::game::Game;
+use crate::image::write_png;
+use crate::pattern::Pattern;
+use crate::render::{Style, BACKGROUND};
+use crate::rle::Rle;
+use crate::rule::Rule;
+use crate::snapshot::Snapshot;
+use crate::standard_error::StandardError;
+use crate::svg::write_svg;
+use crate::viewport::{Framing, Viewport};
+
+use std::error::Error;
+use std::fs;
+use std::io::{Read, Write};
+
+/// A pattern with its rule and generation, to load, step and render without the CLI.
+///
+/// ```
+/// use lifegame::Universe;
+/// use lifegame::render::Style;
+///
+/// let mut universe = Universe::from_rle("x = 3, y = 3\nbo$2bo$3o!", 2).unwrap();
+/// universe.step_n(4);
+/// assert_eq!(universe.population(), 5);
+///
+/// let mut gif = vec![];
+/// universe.render_gif(&mut gif, 8, &Style::default()).unwrap();
+/// assert_eq!(universe.generation(), 11);
+/// ```
+#[derive(Debug)]
+pub struct Universe {
+    game: Game,
+}
+
+/// Frames for `Universe::render`.
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub format: Format,
+    /// Frames to write, the first being the current generation.
+    pub frames: usize,
+    /// Generations between frames.
+    pub every: usize,
+    /// Milliseconds per frame.
+    pub delay: u64,
+    pub framing: Framing,
+    pub style: Style,
+}
+
+impl Default for Recording {
+    fn default() -> Recording {
+        Recording {
+            format: Format::Gif,
+            frames: 100,
+            every: 1,
+            delay: 100,
+            framing: Framing::Full,
+            style: Style::default(),
+        }
+    }
+}
+
+impl Universe {
+    pub fn new(game: Game) -> Universe {
+        Universe { game }
+    }
+
+    /// Parses an RLE pattern, whose header line may be left out, surrounded by `margin` dead cells.
+    pub fn from_rle(body: &str, margin: usize) -> Result<Universe, Box<dyn Error>> {
+        let body = Rle::with_header(body);
+        let (w, h, map) = Rle::from_string(&body, margin)?;
+        let mut game = Game::new(w, h, &map);
+        game.set_rule(Rle::rule(&body)?.unwrap_or_default());
+
+        Ok(Universe { game })
+    }
+
+    /// A snapshot, or an RLE pattern read as `from_rle` does. `margin` only applies to patterns.
+    pub fn from_bytes(bytes: &[u8], margin: usize) -> Result<Universe, Box<dyn Error>> {
+        if Snapshot::matches(bytes) {
+            return Ok(Universe { game: Snapshot::read(&mut &bytes[..])? });
+        }
+
+        let body = std::str::from_utf8(bytes).map_err(|e| StandardError::new(&e.to_string()))?;
+        Universe::from_rle(body, margin)
+    }
+
+    pub fn from_reader<R: Read>(mut r: R, margin: usize) -> Result<Universe, Box<dyn Error>> {
+        let mut bytes = vec![];
+        r.read_to_end(&mut bytes)?;
+        Universe::from_bytes(&bytes, margin)
+    }
+
+    pub fn from_file(filename: &str, margin: usize) -> Result<Universe, Box<dyn Error>> {
+        let bytes = fs::read(filename).map_err(|e| StandardError::new(&format!("{}: {}", filename, e)))?;
+        Universe::from_bytes(&bytes, margin)
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn game_mut(&mut self) -> &mut Game {
+        &mut self.game
+    }
+
+    pub fn into_game(self) -> Game {
+        self.game
+    }
+
+    pub fn width(&self) -> usize {
+        self.game.width as usize
+    }
+
+    pub fn height(&self) -> usize {
+        self.game.height as usize
+    }
+
+    pub fn generation(&self) -> usize {
+        self.game.generation()
+    }
+
+    pub fn population(&self) -> usize {
+        self.game.population()
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.game.rule()
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.game.set_rule(rule);
+    }
+
+    pub fn step(&mut self) {
+        self.game.step();
+    }
+
+    pub fn step_n(&mut self, n: usize) {
+        self.game.step_n(n);
+    }
+
+    /// The cells of the current generation.
+    pub fn pattern(&self) -> Pattern {
+        Pattern::new(self.width(), self.height(), self.game.lives())
+    }
+
+    /// Replaces the cells, keeping the rule and generation.
+    pub fn set_pattern(&mut self, pattern: Pattern) {
+        let mut game = Game::new(pattern.width, pattern.height, &pattern.cells);
+        game.set_rule(self.game.rule());
+        game.resume_at(self.game.generation());
+        self.game = game;
+    }
+
+    pub fn to_rle(&self) -> String {
+        Rle::encode(self.width(), self.height(), &self.game.lives(), &self.game.rule())
+    }
+
+    /// Writes a snapshot that `from_bytes` reads back, generation included.
+    pub fn write_snapshot<W: Write>(&self, mut w: W) -> Result<(), Box<dyn Error>> {
+        Snapshot::write(&self.game, &mut w)
+    }
+
+    /// Draws the current generation as a PNG.
+    pub fn render_png<W: Write>(&self, w: W, style: &Style) -> Result<(), Box<dyn Error>> {
+        write_png(w, self.width(), self.height(), &self.cells(style), style)
+    }
+
+    /// Draws the current generation as an SVG.
+    pub fn render_svg<W: Write>(&self, w: W, style: &Style) -> Result<(), Box<dyn Error>> {
+        let rect = Rect { x: 0, y: 0, width: self.width(), height: self.height() };
+        write_svg(w, &rect, &self.cells(style), style, false)
+    }
+
+    /// Writes `frames` generations from the current one as a GIF, leaving the
+    /// universe at the last of them.
+    pub fn render_gif<W: Write>(&mut self, w: W, frames: usize, style: &Style) -> Result<(), Box<dyn Error>> {
+        self.render(w, &Recording { frames, style: style.clone(), ..Recording::default() })
+    }
+
+    /// Writes an animation as `recording` describes, leaving the universe at its last frame.
+    ///
+    /// Age colors need `game_mut().track_ages(...)` to be called beforehand.
+    pub fn render<W: Write>(&mut self, w: W, recording: &Recording) -> Result<(), Box<dyn Error>> {
+        let (width, height) = (self.width(), self.height());
+        let every = recording.every.max(1);
+        let mut viewport = Viewport::new(recording.framing, width, height, &self.game.lives());
+        let rect = viewport.rect();
+
+        let animation = Animation {
+            width: rect.width,
+            height: rect.height,
+            delay: recording.delay,
+            frames: recording.frames,
+            style: recording.style.clone(),
+        };
+        let mut writer = animation.writer(recording.format, w)?;
+
+        for frame in 0..recording.frames {
+            if frame > 0 {
+                self.game.step_n(every);
+            }
+            let lives = self.game.lives();
+            viewport.update(width, &lives);
+            let cells = recording.style.cells(&lives, self.game.ages());
+            writer.write_frame(&viewport.crop(width, height, &cells, BACKGROUND))?;
+        }
+
+        writer.finish()
+    }
+
+    fn cells(&self, style: &Style) -> Vec<u8> {
+        style.cells(&self.game.lives(), self.game.ages())
+    }
+}
+
+#[test]
+fn test_from_rle() {
+    let universe = Universe::from_rle("#N Glider\nx = 3, y = 3, rule = B36/S23\nbo$2bo$3o!", 1).unwrap();
+    assert_eq!((universe.width(), universe.height()), (5, 5));
+    assert_eq!(universe.population(), 5);
+    assert_eq!(universe.rule().to_string(), "B36/S23");
+
+    let headless = Universe::from_rle("bo$2bo$3o!", 0).unwrap();
+    assert_eq!((headless.width(), headless.height()), (3, 3));
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let mut universe = Universe::from_rle("bo$2bo$3o!", 3).unwrap();
+    universe.step_n(4);
+
+    let mut bytes = vec![];
+    universe.write_snapshot(&mut bytes).unwrap();
+    let restored = Universe::from_bytes(&bytes, 0).unwrap();
+
+    assert_eq!(restored.generation(), 4);
+    assert_eq!(restored.to_rle(), universe.to_rle());
+}
+
+#[test]
+fn test_set_pattern() {
+    let mut universe = Universe::from_rle("x = 3, y = 3, rule = B36/S23\nbo$2bo$3o!", 2).unwrap();
+    universe.step_n(2);
+
+    let trimmed = universe.pattern().trim();
+    universe.set_pattern(trimmed);
+
+    assert_eq!((universe.width(), universe.height()), (3, 3));
+    assert_eq!(universe.generation(), 2);
+    assert_eq!(universe.rule().to_string(), "B36/S23");
+}
+
+#[test]
+fn test_render() {
+    let mut universe = Universe::from_rle("bo$2bo$3o!", 4).unwrap();
+
+    let mut gif = vec![];
+    universe.render_gif(&mut gif, 5, &Style::default()).unwrap();
+    assert!(gif.starts_with(b"GIF89a"));
+    assert_eq!(universe.generation(), 4);
+
+    let recording = Recording { format: Format::WebP, frames: 3, every: 2, ..Recording::default() };
+    universe.render(&mut vec![], &recording).unwrap();
+    assert_eq!(universe.generation(), 8);
+
+    let mut png = vec![];
+    universe.render_png(&mut png, &Style::default()).unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+}
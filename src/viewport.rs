@@ -0,0 +1,242 @@
+use crate::analysis::bounding_box;
+use crate::engine::Rect;
+use crate::standard_error::StandardError;
+
+use std::str::FromStr;
+
+/// How the part of the field to show is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// The whole field.
+    Full,
+    /// The initial bounding box of the pattern, padded by the given cells.
+    Fit(usize),
+    /// A `width` x `height` window kept centered on the live cells.
+    Follow(usize, usize),
+    /// A fixed rectangle.
+    Fixed(Rect),
+}
+
+/// Accepts "full", "fit", "fit:PADDING", "follow:WxH" and "X,Y:WxH".
+impl FromStr for Framing {
+    type Err = StandardError;
+
+    fn from_str(s: &str) -> Result<Framing, StandardError> {
+        let invalid = || StandardError::new(&format!("invalid view: {:?}", s));
+        // An empty view would make for frames without a single pixel.
+        let size = |wh: &str| -> Option<(usize, usize)> {
+            let mut parts = wh.splitn(2, 'x');
+            match (parts.next()?.parse().ok()?, parts.next()?.parse().ok()?) {
+                (0, _) | (_, 0) => None,
+                size => Some(size),
+            }
+        };
+
+        let mut parts = s.splitn(2, ':');
+        let head = parts.next().unwrap_or("");
+        let tail = parts.next();
+
+        match (head, tail) {
+            ("full", None) => Ok(Framing::Full),
+            ("fit", None) => Ok(Framing::Fit(0)),
+            ("fit", Some(padding)) => padding.parse().map(Framing::Fit).map_err(|_| invalid()),
+            ("follow", Some(wh)) => size(wh).map(|(w, h)| Framing::Follow(w, h)).ok_or_else(invalid),
+            (xy, Some(wh)) => {
+                let mut origin = xy.splitn(2, ',');
+                let x = origin.next().and_then(|x| x.parse().ok());
+                let y = origin.next().and_then(|y| y.parse().ok());
+                match (x, y, size(wh)) {
+                    (Some(x), Some(y), Some((width, height))) => Ok(Framing::Fixed(Rect { x, y, width, height })),
+                    _ => Err(invalid())
+                }
+            }
+            _ => Err(invalid())
+        }
+    }
+}
+
+/// The rectangle of a `width` x `height` field that gets rendered.
+///
+/// Its size is settled on creation so every frame of an animation matches;
+/// only `Framing::Follow` moves it afterwards.
+#[derive(Debug, Clone)]
+pub struct Viewport {
+    framing: Framing,
+    rect: Rect,
+}
+
+impl Viewport {
+    pub fn new(framing: Framing, width: usize, height: usize, lives: &[bool]) -> Viewport {
+        let full = Rect { x: 0, y: 0, width, height };
+
+        let rect = match framing {
+            Framing::Full => full,
+            Framing::Fixed(rect) => rect,
+            Framing::Fit(padding) => match bounding_box(width, lives) {
+                Some(b) => Rect {
+                    x: b.x - padding as isize,
+                    y: b.y - padding as isize,
+                    width: b.width + padding * 2,
+                    height: b.height + padding * 2,
+                },
+                None => full
+            },
+            Framing::Follow(w, h) => centered(width, lives, w, h),
+        };
+
+        Viewport { framing, rect }
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Recenters a following viewport on the live cells of the next frame.
+    pub fn update(&mut self, width: usize, lives: &[bool]) {
+        if let Framing::Follow(w, h) = self.framing {
+            if lives.iter().any(|doa| *doa) {
+                self.rect = centered(width, lives, w, h);
+            }
+        }
+    }
+
+    /// Moves the viewport by `dx`, `dy` cells, leaving it fixed there from then on.
+    pub fn pan(&mut self, dx: isize, dy: isize) {
+        self.rect.x += dx;
+        self.rect.y += dy;
+        self.framing = Framing::Fixed(self.rect);
+    }
+
+    /// Changes the size of the viewport around its current center.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let Rect { x, y, width: w, height: h } = self.rect;
+        self.rect = Rect {
+            x: x + (w / 2) as isize - (width / 2) as isize,
+            y: y + (h / 2) as isize - (height / 2) as isize,
+            width,
+            height,
+        };
+
+        self.framing = match self.framing {
+            Framing::Follow(..) => Framing::Follow(width, height),
+            _ => Framing::Fixed(self.rect),
+        };
+    }
+
+    /// Row-major cells under the viewport, treating anything outside the field as dead.
+    pub fn crop<T: Copy>(&self, width: usize, height: usize, cells: &[T], dead: T) -> Vec<T> {
+        let Rect { x, y, width: w, height: h } = self.rect;
+        let mut cropped = Vec::with_capacity(w * h);
+
+        for cy in y..y + h as isize {
+            for cx in x..x + w as isize {
+                let inside = 0 <= cx && cx < width as isize && 0 <= cy && cy < height as isize;
+                cropped.push(if inside { cells[cy as usize * width + cx as usize] } else { dead });
+            }
+        }
+
+        cropped
+    }
+}
+
+fn centered(width: usize, lives: &[bool], w: usize, h: usize) -> Rect {
+    let (sum_x, sum_y, count) = lives.iter().enumerate()
+        .filter(|(_, doa)| **doa)
+        .fold((0, 0, 0), |(sx, sy, n), (i, _)| (sx + i % width, sy + i / width, n + 1));
+
+    let (cx, cy) = match ((sum_x + count / 2).checked_div(count), (sum_y + count / 2).checked_div(count)) {
+        (Some(cx), Some(cy)) => (cx, cy),
+        _ => (width / 2, lives.len() / width.max(1) / 2),
+    };
+
+    Rect { x: cx as isize - (w / 2) as isize, y: cy as isize - (h / 2) as isize, width: w, height: h }
+}
+
+#[test]
+fn test_from_str() {
+    assert_eq!(Framing::from_str("full").unwrap(), Framing::Full);
+    assert_eq!(Framing::from_str("fit").unwrap(), Framing::Fit(0));
+    assert_eq!(Framing::from_str("fit:3").unwrap(), Framing::Fit(3));
+    assert_eq!(Framing::from_str("follow:40x30").unwrap(), Framing::Follow(40, 30));
+    assert_eq!(Framing::from_str("-2,5:10x20").unwrap(), Framing::Fixed(Rect { x: -2, y: 5, width: 10, height: 20 }));
+
+    assert!(Framing::from_str("follow").is_err());
+    assert!(Framing::from_str("follow:40").is_err());
+    assert!(Framing::from_str("follow:0x0").is_err());
+    assert!(Framing::from_str("follow:40x0").is_err());
+    assert!(Framing::from_str("0,0:0x20").is_err());
+    assert!(Framing::from_str("1:10x20").is_err());
+    assert!(Framing::from_str("zoom").is_err());
+}
+
+#[cfg(test)]
+fn glider_in(width: usize, height: usize, x: usize, y: usize) -> Vec<bool> {
+    let mut lives = vec![false; width * height];
+    for (gx, gy) in &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+        lives[(y + gy) * width + x + gx] = true;
+    }
+    lives
+}
+
+#[test]
+fn test_fit() {
+    let lives = glider_in(10, 10, 4, 5);
+
+    let v = Viewport::new(Framing::Fit(1), 10, 10, &lives);
+    assert_eq!(v.rect(), Rect { x: 3, y: 4, width: 5, height: 5 });
+
+    let empty = Viewport::new(Framing::Fit(1), 10, 10, &[false; 100]);
+    assert_eq!(empty.rect(), Rect { x: 0, y: 0, width: 10, height: 10 });
+}
+
+#[test]
+fn test_follow() {
+    let mut v = Viewport::new(Framing::Follow(4, 4), 20, 20, &glider_in(20, 20, 2, 2));
+    assert_eq!(v.rect(), Rect { x: 1, y: 1, width: 4, height: 4 });
+
+    v.update(20, &glider_in(20, 20, 10, 12));
+    assert_eq!(v.rect(), Rect { x: 9, y: 11, width: 4, height: 4 });
+
+    v.update(20, &vec![false; 400]);
+    assert_eq!(v.rect(), Rect { x: 9, y: 11, width: 4, height: 4 });
+}
+
+#[test]
+fn test_pan() {
+    let mut v = Viewport::new(Framing::Follow(4, 4), 20, 20, &glider_in(20, 20, 2, 2));
+    v.pan(3, -2);
+    assert_eq!(v.rect(), Rect { x: 4, y: -1, width: 4, height: 4 });
+
+    v.update(20, &glider_in(20, 20, 10, 12));
+    assert_eq!(v.rect(), Rect { x: 4, y: -1, width: 4, height: 4 });
+}
+
+#[test]
+fn test_resize() {
+    let mut v = Viewport::new(Framing::Full, 10, 10, &[false; 100]);
+    v.resize(20, 4);
+    assert_eq!(v.rect(), Rect { x: -5, y: 3, width: 20, height: 4 });
+
+    let mut v = Viewport::new(Framing::Follow(4, 4), 20, 20, &glider_in(20, 20, 2, 2));
+    v.resize(8, 6);
+    v.update(20, &glider_in(20, 20, 10, 12));
+    assert_eq!(v.rect(), Rect { x: 7, y: 10, width: 8, height: 6 });
+}
+
+#[test]
+fn test_crop() {
+    let lives = vec![
+        true, false, false,
+        false, true, false,
+        false, false, true,
+    ];
+
+    let v = Viewport::new(Framing::Fixed(Rect { x: 1, y: 1, width: 2, height: 2 }), 3, 3, &lives);
+    assert_eq!(v.crop(3, 3, &lives, false), vec![true, false, false, true]);
+
+    let v = Viewport::new(Framing::Fixed(Rect { x: -1, y: 2, width: 3, height: 2 }), 3, 3, &lives);
+    assert_eq!(v.crop(3, 3, &lives, false), vec![false, false, false, false, false, false]);
+
+    let v = Viewport::new(Framing::Full, 3, 3, &lives);
+    assert_eq!(v.crop(3, 3, &lives, false), lives);
+}
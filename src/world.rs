@@ -1,10 +1,12 @@
+use crate::engine::Rect;
+use std::ops::Deref;
+
 #[derive(Debug, Clone)]
 pub struct World {
     width: usize,
-    height: usize,
     w: isize,
     h: isize,
-    pub cells: Vec<bool>,
+    cells: Vec<bool>,
 }
 
 impl World {
@@ -14,7 +16,6 @@ impl World {
 
         World {
             width,
-            height,
             w: width as isize,
             h: height as isize,
             cells,
@@ -29,9 +30,8 @@ impl World {
     }
 
     pub fn set_life(&mut self, x: isize, y: isize, doa: bool) {
-        match self.is_in(x, y) {
-            Ok((x, y)) => unsafe { *self.cells.get_unchecked_mut(self.width * y + x) = doa},
-            _ => ()
+        if let Ok((x, y)) = self.is_in(x, y) {
+            unsafe { *self.cells.get_unchecked_mut(self.width * y + x) = doa }
         }
     }
 
@@ -44,6 +44,43 @@ impl World {
         }
     }
 
+    /// Sets every listed `(x, y)` to `doa`, ignoring coordinates outside the world.
+    pub fn set_cells(&mut self, coords: &[(isize, isize)], doa: bool) {
+        for (x, y) in coords {
+            self.set_life(*x, *y, doa);
+        }
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|doa| **doa).count()
+    }
+
+    /// Same as `to_vec` without the copy.
+    pub fn iter(&self) -> impl Iterator<Item=bool> + '_ {
+        self.cells.iter().cloned()
+    }
+
+    /// Row-major alive/dead flags of the whole world.
+    pub fn to_vec(&self) -> Vec<bool> {
+        self.cells.clone()
+    }
+
+    /// Indices of the cells that differ between `self` and `other`.
+    pub fn changes(&self, other: &World) -> Vec<usize> {
+        self.cells.iter().zip(other.cells.iter()).enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn toggle(&mut self, indices: &[usize]) {
+        for i in indices {
+            if let Some(doa) = self.cells.get_mut(*i) {
+                *doa = !*doa;
+            }
+        }
+    }
+
     fn is_in(&self, x: isize, y: isize) -> Result<(usize, usize), ()> {
         if x < 0 || self.w <= x || y < 0 || self.h <= y {
             return Err(())
@@ -53,6 +90,60 @@ impl World {
     }
 }
 
+/// Live cells of a world as `(x, y)` in row-major order, over a borrow or a lock guard of it.
+pub struct LiveCells<W> {
+    world: W,
+    left: isize,
+    right: isize,
+    bottom: isize,
+    x: isize,
+    y: isize,
+}
+
+impl<W: Deref<Target=World>> LiveCells<W> {
+    pub fn all(world: W) -> LiveCells<W> {
+        let rect = Rect { x: 0, y: 0, width: world.w as usize, height: world.h as usize };
+        LiveCells::within(world, rect)
+    }
+
+    /// Live cells within `rect`, clipped to the world.
+    pub fn within(world: W, rect: Rect) -> LiveCells<W> {
+        let left = rect.x.clamp(0, world.w);
+        let right = (rect.x + rect.width as isize).clamp(0, world.w);
+        let top = rect.y.clamp(0, world.h);
+        let bottom = (rect.y + rect.height as isize).clamp(0, world.h);
+
+        LiveCells {
+            world,
+            left,
+            right,
+            bottom: if left < right { bottom } else { top },
+            x: left,
+            y: top,
+        }
+    }
+}
+
+impl<W: Deref<Target=World>> Iterator for LiveCells<W> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        while self.y < self.bottom {
+            let (x, y) = (self.x, self.y);
+            self.x += 1;
+            if self.x == self.right {
+                self.x = self.left;
+                self.y += 1;
+            }
+            if self.world.is_live(x, y) {
+                return Some((x as usize, y as usize));
+            }
+        }
+
+        None
+    }
+}
+
 #[test]
 fn test_is_in() {
     let w = World::new(10, 10);
@@ -67,6 +158,7 @@ fn test_is_in() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_is_live() {
     let mut w = World::new(10, 10);
 
@@ -76,6 +168,7 @@ fn test_is_live() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_set_vec_live() {
     let mut w = World::new(4, 3);
 
@@ -95,9 +188,55 @@ fn test_set_vec_live() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_dead_or_alive() {
     let mut w = World::new(10, 10);
 
     w.set_life(9, 9, true);
     assert_eq!(w.cells[99], true);
 }
+
+#[test]
+fn test_changes_and_toggle() {
+    let mut a = World::new(3, 2);
+    let mut b = World::new(3, 2);
+
+    a.set_lives(0, 0, vec![true, false, true, false, true, false]);
+    b.set_lives(0, 0, vec![true, true, false, false, true, true]);
+
+    let changes = a.changes(&b);
+    assert_eq!(changes, vec![1, 2, 5]);
+
+    a.toggle(&changes);
+    assert_eq!(a.cells, b.cells);
+}
+
+#[test]
+fn test_live_cells() {
+    let mut w = World::new(4, 3);
+
+    w.set_cells(&[(1, 0), (3, 1), (0, 2), (9, 9), (-1, 0)], true);
+    assert_eq!(w.population(), 3);
+    assert_eq!(LiveCells::all(&w).collect::<Vec<_>>(), vec![(1, 0), (3, 1), (0, 2)]);
+
+    w.set_cells(&[(3, 1)], false);
+    assert_eq!(LiveCells::all(&w).collect::<Vec<_>>(), vec![(1, 0), (0, 2)]);
+}
+
+#[test]
+fn test_live_cells_in() {
+    let mut w = World::new(4, 4);
+    w.set_cells(&[(0, 0), (1, 1), (2, 2), (3, 3)], true);
+
+    let inner = Rect { x: 1, y: 1, width: 2, height: 2 };
+    assert_eq!(LiveCells::within(&w, inner).collect::<Vec<_>>(), vec![(1, 1), (2, 2)]);
+
+    let overhang = Rect { x: -2, y: -2, width: 4, height: 4 };
+    assert_eq!(LiveCells::within(&w, overhang).collect::<Vec<_>>(), vec![(0, 0), (1, 1)]);
+
+    let outside = Rect { x: 5, y: 0, width: 2, height: 2 };
+    assert_eq!(LiveCells::within(&w, outside).count(), 0);
+
+    let empty = Rect { x: 2, y: 0, width: 0, height: 4 };
+    assert_eq!(LiveCells::within(&w, empty).count(), 0);
+}
@@ -0,0 +1,45 @@
+//! The `lifegame` binary, run the way a shell would.
+
+#![cfg(feature = "cli")]
+
+use lifegame::rle::Rle;
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn lifegame(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_lifegame")).args(args).output().unwrap()
+}
+
+/// Empty directory of its own for each test, so they can run in parallel.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lifegame-cli-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_convert_inline_pattern() {
+    let dir = scratch("convert-inline");
+    let output = dir.join("glider.rle");
+
+    let result = lifegame(&["convert", "--rle", "bo$2bo$3o!", "--rotate", "180", "--output", output.to_str().unwrap()]);
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+
+    let written = fs::read_to_string(&output).unwrap();
+    assert_eq!(Rle::live_cells(&written).unwrap(), vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 2)]);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_convert_bad_option_writes_nothing() {
+    let dir = scratch("convert-bad-option");
+    let output = dir.join("glider.png");
+
+    let result = lifegame(&["convert", "--rle", "bo$2bo$3o!", "--scale", "0", "-o", output.to_str().unwrap()]);
+    assert!(!result.status.success());
+    assert!(!output.exists());
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,190 @@
+//! Well-known patterns from `fixtures/conformance`, run with every way of stepping a `Game`.
+//!
+//! `expected.txt` lists the margin each pattern is padded with, the generation
+//! to run to and the population there, and `<pattern>.final.rle` holds the
+//! whole field at that generation. How those were checked is noted in `expected.txt`.
+//!
+//! The bounded backends take minutes to run the R-pentomino to generation 1103
+//! in a debug build, so by default `step` only takes it to generation 300 on a
+//! field fitted to that, checked against the unbounded stepper; the full run on
+//! every backend is left to `cargo test --release -- --ignored`.
+
+use lifegame::game::Game;
+use lifegame::rle::Rle;
+use lifegame::rule::Rule;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::mpsc::channel;
+
+const FIXTURES: &str = "fixtures/conformance";
+
+struct Case {
+    margin: usize,
+    generations: usize,
+    population: usize,
+}
+
+fn case(name: &str) -> Case {
+    let manifest = fs::read_to_string(format!("{}/expected.txt", FIXTURES)).unwrap();
+    let columns: Vec<usize> = manifest.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .find(|columns| columns.first() == Some(&name))
+        .unwrap_or_else(|| panic!("{} is not in expected.txt", name))[1..]
+        .iter()
+        .map(|column| column.parse().unwrap())
+        .collect();
+
+    Case { margin: columns[0], generations: columns[1], population: columns[2] }
+}
+
+fn load(name: &str, margin: usize) -> Game {
+    let body = fs::read_to_string(format!("{}/{}.rle", FIXTURES, name)).unwrap();
+    let (w, h, map) = Rle::from_string(&body, margin).unwrap();
+    let mut game = Game::new(w, h, &map);
+    game.set_rule(Rle::rule(&body).unwrap().unwrap_or_default());
+    game
+}
+
+fn serial(mut game: Game, generations: usize) -> Vec<bool> {
+    game.step_n(generations);
+    game.lives()
+}
+
+fn farm(mut game: Game, generations: usize) -> Vec<bool> {
+    game.set_threads(4);
+    let (trigger, triggers) = channel();
+    let (_game, results) = game.step_farm(triggers);
+
+    loop {
+        let (generation, lives) = results.recv().unwrap();
+        if generation >= generations {
+            return lives;
+        }
+        trigger.send(()).unwrap();
+    }
+}
+
+/// Steps forward again over recorded history, which replays changes instead of computing them.
+fn replay(mut game: Game, generations: usize) -> Vec<bool> {
+    game.set_history_depth(generations);
+    game.step_n(generations);
+    assert!(game.jump_to(0));
+    game.step_n(generations);
+    game.lives()
+}
+
+/// Live cells on an unbounded plane, as a check that the field edge did not get in the way.
+fn unbounded(game: Game, generations: usize) -> Vec<bool> {
+    let (width, height, rule) = (game.width as usize, game.height as usize, game.rule());
+    let mut cells: HashSet<(isize, isize)> = game.live_cells().map(|(x, y)| (x as isize, y as isize)).collect();
+
+    for _ in 0..generations {
+        cells = next(&cells, &rule);
+    }
+
+    let mut lives = vec![false; width * height];
+    for (x, y) in cells {
+        assert!(0 < x && x < width as isize - 1 && 0 < y && y < height as isize - 1, "({}, {}) reached the edge", x, y);
+        lives[y as usize * width + x as usize] = true;
+    }
+    lives
+}
+
+fn next(cells: &HashSet<(isize, isize)>, rule: &Rule) -> HashSet<(isize, isize)> {
+    let mut neighbours = HashMap::new();
+    for (x, y) in cells {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if (dx, dy) != (0, 0) {
+                    *neighbours.entry((x + dx, y + dy)).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    let mut candidates: HashSet<(isize, isize)> = neighbours.keys().cloned().collect();
+    candidates.extend(cells);
+    candidates.into_iter()
+        .filter(|cell| rule.next(cells.contains(cell), *neighbours.get(cell).unwrap_or(&0)))
+        .collect()
+}
+
+/// Runs a game for some generations and returns its cells.
+type Backend = fn(Game, usize) -> Vec<bool>;
+
+const BACKENDS: [(&str, Backend); 4] = [
+    ("step", serial),
+    ("step_farm", farm),
+    ("replay", replay),
+    ("unbounded", unbounded),
+];
+
+fn conform(name: &str) {
+    conform_with(name, &BACKENDS);
+}
+
+fn conform_with(name: &str, backends: &[(&str, Backend)]) {
+    let case = case(name);
+    let expected = fs::read_to_string(format!("{}/{}.final.rle", FIXTURES, name)).unwrap();
+
+    for (backend, run) in backends {
+        let game = load(name, case.margin);
+        let (width, height, rule) = (game.width as usize, game.height as usize, game.rule());
+        let lives = run(game, case.generations);
+
+        assert_eq!(lives.iter().filter(|doa| **doa).count(), case.population, "{} population with {}", name, backend);
+        assert_eq!(Rle::encode(width, height, &lives, &rule).trim_end(), expected.trim_end(), "{} cells with {}", name, backend);
+    }
+}
+
+#[test]
+fn test_glider() {
+    conform("glider");
+}
+
+#[test]
+fn test_lwss() {
+    conform("lwss");
+}
+
+#[test]
+fn test_gosper_gun() {
+    conform("gosper_gun");
+}
+
+#[test]
+fn test_r_pentomino() {
+    conform_with("r_pentomino", &[("unbounded", unbounded)]);
+}
+
+#[test]
+fn test_r_pentomino_early() {
+    // By generation 300 the R-pentomino reaches at most 63 cells beyond its 3 x 3 start.
+    let (margin, generations) = (64, 300);
+
+    let lives = serial(load("r_pentomino", margin), generations);
+    assert_eq!(lives, unbounded(load("r_pentomino", margin), generations));
+    assert_eq!(lives.iter().filter(|doa| **doa).count(), 168);
+}
+
+#[test]
+#[ignore]
+fn test_r_pentomino_every_backend() {
+    conform("r_pentomino");
+}
+
+#[test]
+fn test_acorn() {
+    conform("acorn");
+}
+
+#[test]
+fn test_diehard() {
+    conform("diehard");
+
+    let case = case("diehard");
+    let mut game = load("diehard", case.margin);
+    game.step_n(case.generations - 1);
+    assert!(game.population() > 0);
+}
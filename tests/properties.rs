@@ -0,0 +1,117 @@
+//! Randomized checks that the stepping backends agree with each other and with
+//! symmetries of the rules, and that RLE keeps every cell.
+
+use lifegame::game::Game;
+use lifegame::pattern::Pattern;
+use lifegame::rle::Rle;
+use lifegame::rule::Rule;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use std::sync::mpsc::channel;
+
+/// Fields of up to 12 x 12 cells.
+fn pattern() -> impl Strategy<Value = Pattern> {
+    (1usize..=12, 1usize..=12).prop_flat_map(|(width, height)| {
+        vec(any::<bool>(), width * height).prop_map(move |cells| Pattern::new(width, height, cells))
+    })
+}
+
+/// Any birth and survival counts from 0 to 8.
+fn rule() -> impl Strategy<Value = Rule> {
+    (0u16..0x200, 0u16..0x200).prop_map(|(birth, survival)| Rule::from_masks(birth, survival))
+}
+
+fn game(pattern: &Pattern, rule: Rule) -> Game {
+    let mut game = Game::new(pattern.width, pattern.height, &pattern.cells);
+    game.set_rule(rule);
+    game
+}
+
+fn stepped(pattern: &Pattern, rule: Rule, generations: usize) -> Pattern {
+    let mut game = game(pattern, rule);
+    game.step_n(generations);
+    Pattern::new(pattern.width, pattern.height, game.lives())
+}
+
+/// One generation computed by counting neighbours and looking them up in the
+/// rule's masks, sharing no code with `Game`.
+fn by_count(pattern: &Pattern, rule: &Rule) -> Pattern {
+    let (width, height) = (pattern.width as isize, pattern.height as isize);
+    let live = |x: isize, y: isize| 0 <= x && x < width && 0 <= y && y < height && pattern.cells[(y * width + x) as usize];
+    let (birth, survival) = rule.masks();
+
+    let mut cells = vec![];
+    for y in 0..height {
+        for x in 0..width {
+            let mut neighbours = 0;
+            for (dx, dy) in &[(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                if live(x + dx, y + dy) {
+                    neighbours += 1;
+                }
+            }
+            let mask = if live(x, y) { survival } else { birth };
+            cells.push(mask & 1 << neighbours != 0);
+        }
+    }
+    Pattern::new(pattern.width, pattern.height, cells)
+}
+
+proptest! {
+    #[test]
+    fn step_farm_matches_step(pattern in pattern(), rule in rule(), threads in 1usize..=4, generations in 1usize..=5) {
+        let mut farmed = game(&pattern, rule);
+        farmed.set_threads(threads);
+        let (trigger, triggers) = channel();
+        let (_game, results) = farmed.step_farm(triggers);
+
+        let mut lives = results.recv().unwrap().1;
+        for _ in 0..generations {
+            trigger.send(()).unwrap();
+            lives = results.recv().unwrap().1;
+        }
+
+        prop_assert_eq!(lives, stepped(&pattern, rule, generations).cells);
+    }
+
+    #[test]
+    fn step_matches_neighbour_count(pattern in pattern(), rule in rule(), generations in 1usize..=5) {
+        let mut expected = pattern.clone();
+        for _ in 0..generations {
+            expected = by_count(&expected, &rule);
+        }
+
+        prop_assert_eq!(stepped(&pattern, rule, generations), expected);
+    }
+
+    #[test]
+    fn step_back_undoes_step(pattern in pattern(), rule in rule(), generations in 1usize..=5) {
+        let mut game = game(&pattern, rule);
+        game.set_history_depth(generations);
+        game.step_n(generations);
+
+        prop_assert!(game.jump_to(0));
+        prop_assert_eq!(game.lives(), pattern.cells.clone());
+        game.step_n(generations);
+        prop_assert_eq!(game.lives(), stepped(&pattern, rule, generations).cells);
+    }
+
+    #[test]
+    fn symmetries_commute_with_step(pattern in pattern(), rule in rule(), quarters in 0usize..4, flip in any::<bool>()) {
+        let transform = |p: &Pattern| {
+            let turned = p.rotate(quarters);
+            if flip { turned.flip_horizontal() } else { turned }
+        };
+
+        prop_assert_eq!(stepped(&transform(&pattern), rule, 1), transform(&stepped(&pattern, rule, 1)));
+    }
+
+    #[test]
+    fn rle_round_trips(pattern in pattern(), rule in rule()) {
+        let body = Rle::encode(pattern.width, pattern.height, &pattern.cells, &rule);
+        let (width, height, cells) = Rle::from_string(&body, 0).unwrap();
+
+        prop_assert_eq!(Pattern::new(width, height, cells), pattern);
+        prop_assert_eq!(Rle::rule(&body).unwrap(), Some(rule));
+    }
+}